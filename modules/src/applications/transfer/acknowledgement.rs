@@ -3,16 +3,18 @@ use crate::core::ics26_routing::context::Acknowledgement as AckTrait;
 use crate::prelude::*;
 use core::fmt::{Display, Formatter};
 
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// A string constant included in error acknowledgements.
 /// NOTE: Changing this const is state machine breaking as acknowledgements are written into state
 pub const ACK_ERR_STR: &str = "error handling packet on destination chain: see events for details";
 pub const ACK_SUCCESS_B64: &[u8] = b"AQ==";
 
+/// The ICS20 acknowledgement, encoded as the JSON form of the Cosmos SDK's protobuf
+/// `Acknowledgement`, i.e. `{"result":"AQ=="}` or `{"error":"..."}`.
 #[derive(Clone, Debug)]
 pub enum Acknowledgement {
-    /// Equivalent to b"AQ==" (i.e. `base64::encode(0x01)`)
+    /// The base64 encoded result, equivalent to b"AQ==" (i.e. `base64::encode(0x01)`)
     Success(Vec<u8>),
     /// Error Acknowledgement
     Error(String),
@@ -26,24 +28,103 @@ impl Acknowledgement {
     pub fn from_error(err: Error) -> Self {
         Self::Error(format!("{}: {}", ACK_ERR_STR, err))
     }
+
+    /// Returns the canonical bytes of a success acknowledgement, i.e. `{"result":"AQ=="}`.
+    pub fn success_bytes() -> Vec<u8> {
+        Self::success().into()
+    }
+
+    /// Returns the canonical bytes of an error acknowledgement with the given message, i.e.
+    /// `{"error":"..."}`.
+    pub fn error_bytes(msg: &str) -> Vec<u8> {
+        Self::Error(msg.to_string()).into()
+    }
 }
 
-impl AsRef<[u8]> for Acknowledgement {
-    fn as_ref(&self) -> &[u8] {
-        match self {
-            Acknowledgement::Success(b) => b.as_slice(),
-            Acknowledgement::Error(s) => s.as_bytes(),
+impl TryFrom<Vec<u8>> for Acknowledgement {
+    type Error = Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        if bytes.is_empty() {
+            return Err(Error::empty_acknowledgement());
         }
+        serde_json::from_slice(&bytes).map_err(|_| Error::ack_deserialization())
+    }
+}
+
+impl From<Acknowledgement> for Vec<u8> {
+    fn from(ack: Acknowledgement) -> Self {
+        let json = serde_json::to_string(&ack)
+            .expect("Acknowledgement's infallible Serialize impl failed");
+
+        // The SDK sorts the ack JSON by round-tripping it through Go's `encoding/json`, which
+        // escapes these characters, so they must be escaped for the bytes to match.
+        let mut escaped = String::with_capacity(json.len());
+        for c in json.chars() {
+            match c {
+                '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => {
+                    escaped.push_str(&format!("\\u{:04x}", c as u32))
+                }
+                c => escaped.push(c),
+            }
+        }
+        escaped.into_bytes()
+    }
+}
+
+/// An ICS20 acknowledgement along with its canonical encoding (see `Acknowledgement::into`),
+/// which is what is exposed through `AsRef<[u8]>` and hence committed by the host.
+#[derive(Clone, Debug)]
+pub struct EncodedAcknowledgement {
+    ack: Acknowledgement,
+    bytes: Vec<u8>,
+}
+
+impl EncodedAcknowledgement {
+    pub fn acknowledgement(&self) -> &Acknowledgement {
+        &self.ack
+    }
+}
+
+impl From<Acknowledgement> for EncodedAcknowledgement {
+    fn from(ack: Acknowledgement) -> Self {
+        let bytes = ack.clone().into();
+        Self { ack, bytes }
+    }
+}
+
+impl AsRef<[u8]> for EncodedAcknowledgement {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// The wire format of the ICS20 acknowledgement, serialized by serde as `{"result":...}` or
+/// `{"error":...}`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RawAcknowledgement {
+    Result(String),
+    Error(String),
+}
+
+impl Serialize for Acknowledgement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let raw = match self {
+            Acknowledgement::Success(b) => {
+                RawAcknowledgement::Result(String::from_utf8_lossy(b).into_owned())
+            }
+            Acknowledgement::Error(s) => RawAcknowledgement::Error(s.clone()),
+        };
+        raw.serialize(serializer)
     }
 }
 
 impl<'de> Deserialize<'de> for Acknowledgement {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let s = String::deserialize(deserializer)?;
-        let ack = if s.as_bytes() == ACK_SUCCESS_B64 {
-            Self::Success(ACK_SUCCESS_B64.to_vec())
-        } else {
-            Self::Error(s)
+        let ack = match RawAcknowledgement::deserialize(deserializer)? {
+            RawAcknowledgement::Result(result) => Self::Success(result.into_bytes()),
+            RawAcknowledgement::Error(err) => Self::Error(err),
         };
         Ok(ack)
     }
@@ -58,4 +139,80 @@ impl Display for Acknowledgement {
     }
 }
 
-impl AckTrait for Acknowledgement {}
+impl AckTrait for EncodedAcknowledgement {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::transfer::error::ErrorDetail;
+
+    #[test]
+    fn test_ack_try_from_bytes() {
+        let err = Acknowledgement::try_from(vec![]).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::EmptyAcknowledgement(_)));
+
+        let ack = Acknowledgement::try_from(br#"{"result":"AQ=="}"#.to_vec()).unwrap();
+        assert!(matches!(ack, Acknowledgement::Success(b) if b == ACK_SUCCESS_B64));
+
+        let ack = Acknowledgement::try_from(br#"{"error":"failed"}"#.to_vec()).unwrap();
+        assert!(matches!(ack, Acknowledgement::Error(s) if s == "failed"));
+
+        let err = Acknowledgement::try_from(br#""AQ==""#.to_vec()).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::AckDeserialization(_)));
+    }
+
+    #[test]
+    fn test_ack_exact_bytes() {
+        let success: Vec<u8> = Acknowledgement::success().into();
+        assert_eq!(success, br#"{"result":"AQ=="}"#.to_vec());
+
+        let error: Vec<u8> = Acknowledgement::Error("insufficient funds".to_string()).into();
+        assert_eq!(error, br#"{"error":"insufficient funds"}"#.to_vec());
+
+        for bytes in [success, error] {
+            let ack = Acknowledgement::try_from(bytes.clone()).unwrap();
+            assert_eq!(Vec::<u8>::from(ack), bytes);
+        }
+    }
+
+    #[test]
+    fn test_ack_success_and_error_bytes() {
+        assert_eq!(
+            Acknowledgement::success_bytes(),
+            br#"{"result":"AQ=="}"#.to_vec()
+        );
+
+        // the error ack written by ibc-go for a failed packet
+        assert_eq!(
+            Acknowledgement::error_bytes(
+                "ABCI code: 1: error handling packet: see events for details"
+            ),
+            br#"{"error":"ABCI code: 1: error handling packet: see events for details"}"#.to_vec()
+        );
+
+        // Go's `encoding/json` escapes HTML characters
+        let bytes = Acknowledgement::error_bytes("1 < 2 && 2 > 1");
+        assert_eq!(
+            bytes,
+            br#"{"error":"1 \u003c 2 \u0026\u0026 2 \u003e 1"}"#.to_vec()
+        );
+        let ack = Acknowledgement::try_from(bytes).unwrap();
+        assert!(matches!(ack, Acknowledgement::Error(s) if s == "1 < 2 && 2 > 1"));
+    }
+
+    #[test]
+    fn test_encoded_ack_bytes() {
+        let success = EncodedAcknowledgement::from(Acknowledgement::success());
+        assert_eq!(
+            success.as_ref(),
+            Acknowledgement::success_bytes().as_slice()
+        );
+        assert!(matches!(
+            success.acknowledgement(),
+            Acknowledgement::Success(_)
+        ));
+
+        let error = EncodedAcknowledgement::from(Acknowledgement::Error("failed".to_string()));
+        assert_eq!(error.as_ref(), br#"{"error":"failed"}"#);
+    }
+}
@@ -1,14 +1,20 @@
+use alloc::collections::btree_set::BTreeSet;
+use core::str::FromStr;
+
 use sha2::{Digest, Sha256};
-use subtle_encoding::hex;
+use subtle_encoding::{bech32, hex};
 
 use super::error::Error as Ics20Error;
-use crate::applications::transfer::acknowledgement::Acknowledgement;
+use crate::applications::transfer::acknowledgement::{Acknowledgement, EncodedAcknowledgement};
 use crate::applications::transfer::events::{AckEvent, AckStatusEvent, RecvEvent, TimeoutEvent};
-use crate::applications::transfer::packet::PacketData;
+use crate::applications::transfer::packet::{ForwardMemo, PacketData, PacketDataLimits};
 use crate::applications::transfer::relay::on_ack_packet::process_ack_packet;
 use crate::applications::transfer::relay::on_recv_packet::process_recv_packet;
 use crate::applications::transfer::relay::on_timeout_packet::process_timeout_packet;
-use crate::applications::transfer::{PrefixedCoin, PrefixedDenom, VERSION};
+use crate::applications::transfer::{
+    Amount, DenomTrace, HashedDenom, IbcDenomHash, PrefixedCoin, PrefixedDenom, TracePrefix,
+    VERSION,
+};
 use crate::core::ics04_channel::channel::{Counterparty, Order};
 use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
 use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
@@ -24,6 +30,30 @@ pub trait Ics20Keeper:
     ChannelKeeper + BankKeeper<AccountId = <Self as Ics20Keeper>::AccountId>
 {
     type AccountId;
+
+    /// Stores the denomination trace under its hash (see `PrefixedDenom::hashed`).
+    /// Implement only if the host chain supports hashed denominations.
+    fn set_denom_trace(&mut self, _denom_trace: &DenomTrace) -> Result<(), Ics20Error> {
+        Ok(())
+    }
+
+    /// Returns all stored denomination traces along with their hashes, e.g. for genesis export.
+    /// Implement only if the host chain supports exporting denomination traces.
+    fn export_denom_traces(&self) -> Vec<(HashedDenom, DenomTrace)> {
+        vec![]
+    }
+
+    /// Stores the given denomination traces under their respective hashes, e.g. on genesis import.
+    /// Fails with `Ics20Error::mismatched_denom_trace_hash` without storing any trace if the hash of
+    /// an entry is not the hash of its trace (see `PrefixedDenom::hashed`), so that corrupted
+    /// entries cannot shadow other traces.
+    /// Implement only if the host chain supports importing denomination traces.
+    fn import_denom_traces(
+        &mut self,
+        _entries: Vec<(HashedDenom, DenomTrace)>,
+    ) -> Result<(), Ics20Error> {
+        Ok(())
+    }
 }
 
 pub trait Ics20Reader: ChannelReader + PortReader {
@@ -32,13 +62,19 @@ pub trait Ics20Reader: ChannelReader + PortReader {
     /// get_port returns the portID for the transfer module.
     fn get_port(&self) -> Result<PortId, Ics20Error>;
 
+    /// Returns the ICS20 version negotiated on transfer channels, from which escrow addresses are
+    /// also derived. Override only if the host chain runs a custom version.
+    fn version(&self) -> &str {
+        VERSION
+    }
+
     /// Returns the escrow account id for a port and channel combination
     fn get_channel_escrow_address(
         &self,
         port_id: &PortId,
         channel_id: ChannelId,
     ) -> Result<<Self as Ics20Reader>::AccountId, Ics20Error> {
-        let hash = cosmos_adr028_escrow_address(port_id, channel_id);
+        let hash = cosmos_adr028_escrow_address(self.version(), port_id, channel_id);
         String::from_utf8(hex::encode_upper(hash))
             .expect("hex encoded bytes are not valid UTF8")
             .parse::<Signer>()
@@ -47,25 +83,158 @@ pub trait Ics20Reader: ChannelReader + PortReader {
             .map_err(|_| Ics20Error::parse_account_failure())
     }
 
+    /// Returns all the coins held in the escrow account of a port and channel combination, or
+    /// `None` (the default) if the host chain does not support listing escrowed balances.
+    /// Implement only if the host chain supports it, in which case received tokens are only
+    /// unescrowed if the escrow account holds enough of them.
+    fn channel_escrow_balances(
+        &self,
+        _port_id: &PortId,
+        _channel_id: ChannelId,
+    ) -> Option<Vec<PrefixedCoin>> {
+        None
+    }
+
+    /// Returns the amount of `denom` held in the escrow account of a port and channel combination,
+    /// as listed by `channel_escrow_balances`.
+    fn channel_escrow_balance(
+        &self,
+        port_id: &PortId,
+        channel_id: ChannelId,
+        denom: &PrefixedDenom,
+    ) -> Option<Amount> {
+        let balances = self.channel_escrow_balances(port_id, channel_id)?;
+        Some(
+            balances
+                .into_iter()
+                .find(|coin| &coin.denom == denom)
+                .map_or(Amount::ZERO, |coin| coin.amount),
+        )
+    }
+
+    /// Returns true if the counterparty chain of the given port and channel can only represent
+    /// amounts that fit in a `u128`, in which case larger transfers are rejected at send time.
+    fn is_counterparty_u128_bounded(&self, _port_id: &PortId, _channel_id: ChannelId) -> bool {
+        false
+    }
+
+    /// Returns true if tokens whose base denomination is a hashed IBC denomination (see
+    /// `PrefixedDenom::has_hashed_base`) must be rejected at send time. Otherwise, such transfers
+    /// are allowed but a warning is logged.
+    fn is_strict_denom_check(&self) -> bool {
+        false
+    }
+
+    /// Returns the set of trace prefixes (i.e. port and channel hops) that are trusted in strict
+    /// receive mode, in which vouchers are minted only if every hop of their trace is trusted.
+    /// Returns `None` (the default) to accept vouchers with any trace.
+    fn known_trace_prefixes(&self) -> Option<BTreeSet<TracePrefix>> {
+        None
+    }
+
+    /// Returns the number of decimals by which the sending chain's representation of `denom`
+    /// exceeds the local one, if received amounts of `denom` that would lose precision locally must
+    /// be rejected. Such amounts must then be a multiple of `10^excess_decimals`.
+    /// Returns `None` (the default) to accept any amount.
+    fn excess_recv_decimals(&self, _denom: &PrefixedDenom) -> Option<u8> {
+        None
+    }
+
+    /// Returns true if tokens of the given denomination must not be received, e.g. because they are
+    /// compromised assets. `denom` is the denomination as credited on this chain (i.e. after
+    /// removing or adding the packet's prefix).
+    fn is_blocked_denom(&self, _denom: &PrefixedDenom) -> bool {
+        false
+    }
+
+    /// Returns true if tokens must not be sent to `receiver`, e.g. because it is an all-zero or a
+    /// known burn address to which transfers are almost always a mistake.
+    fn is_forbidden_recipient(&self, _receiver: &Signer) -> bool {
+        false
+    }
+
+    /// Returns the maximum lengths of the sender, receiver and memo of sent and received packets.
+    fn packet_data_limits(&self) -> PacketDataLimits {
+        PacketDataLimits::default()
+    }
+
+    /// Returns the signer that received tokens are credited to, given the receiver address
+    /// specified in the packet data. Implement only if the host chain needs to translate
+    /// addresses from a foreign format (e.g. hex-encoded EVM addresses to bech32).
+    fn translate_receiver(&self, raw: &str) -> Result<Signer, Ics20Error> {
+        raw.parse().map_err(Ics20Error::signer)
+    }
+
+    /// Returns the total supply of the given voucher denomination.
+    /// Implement only if the host chain caps the supply of vouchers, see `voucher_supply_cap`.
+    fn voucher_supply(&self, _denom: &PrefixedDenom) -> Amount {
+        Amount::ZERO
+    }
+
+    /// Returns the maximum total supply of the given voucher denomination, if capped. Receiving
+    /// vouchers whose minting would exceed the cap is rejected.
+    /// Implement only if the host chain caps the supply of vouchers.
+    fn voucher_supply_cap(&self, _denom: &PrefixedDenom) -> Option<Amount> {
+        None
+    }
+
     /// Returns true iff send is enabled.
     fn is_send_enabled(&self) -> bool;
 
     /// Returns true iff receive is enabled.
     fn is_receive_enabled(&self) -> bool;
 
+    /// Returns true iff sending tokens of the given denomination is enabled.
+    /// Implement only if the host chain enables sending per denomination, by default this is
+    /// `is_send_enabled`.
+    fn is_send_enabled_for(&self, _denom: &PrefixedDenom) -> bool {
+        self.is_send_enabled()
+    }
+
+    /// Returns true iff receiving tokens of the given denomination, as credited on this chain (i.e.
+    /// after the packet's trace prefix is removed or added), is enabled.
+    /// Implement only if the host chain enables receiving per denomination, by default this is
+    /// `is_receive_enabled`.
+    fn is_receive_enabled_for(&self, _denom: &PrefixedDenom) -> bool {
+        self.is_receive_enabled()
+    }
+
     /// Returns a hash of the prefixed denom.
     /// Implement only if the host chain supports hashed denominations.
     fn denom_hash_string(&self, _denom: &PrefixedDenom) -> Option<String> {
         None
     }
+
+    /// Returns the denomination trace stored under the given hash, if any.
+    /// Implement only if the host chain supports hashed denominations.
+    fn get_denom_trace(&self, _denom_hash: &HashedDenom) -> Option<DenomTrace> {
+        None
+    }
+
+    /// Returns true if a denomination trace is stored under the given hash.
+    fn has_denom_trace(&self, denom_hash: &HashedDenom) -> bool {
+        self.get_denom_trace(denom_hash).is_some()
+    }
+
+    /// Resolves `denom` to its full denomination: a hashed denomination (i.e. `ibc/{hash}`) is
+    /// looked up in the stored denomination traces, any other denomination is parsed directly.
+    /// Returns `None` if the hash is unknown or if the denomination is invalid.
+    fn resolve_denom(&self, denom: &str) -> Option<PrefixedDenom> {
+        if denom.starts_with("ibc/") {
+            let hash = IbcDenomHash::from_str(denom).ok()?;
+            self.get_denom_trace(hash.hashed_denom())
+        } else {
+            PrefixedDenom::from_str(denom).ok()
+        }
+    }
 }
 
 // https://github.com/cosmos/cosmos-sdk/blob/master/docs/architecture/adr-028-public-key-addresses.md
-fn cosmos_adr028_escrow_address(port_id: &PortId, channel_id: ChannelId) -> Vec<u8> {
+fn cosmos_adr028_escrow_address(version: &str, port_id: &PortId, channel_id: ChannelId) -> Vec<u8> {
     let contents = format!("{}/{}", port_id, channel_id);
 
     let mut hasher = Sha256::new();
-    hasher.update(VERSION.as_bytes());
+    hasher.update(version.as_bytes());
     hasher.update([0]);
     hasher.update(contents.as_bytes());
 
@@ -74,6 +243,69 @@ fn cosmos_adr028_escrow_address(port_id: &PortId, channel_id: ChannelId) -> Vec<
     hash
 }
 
+/// Computes the escrow account of the given port and channel offline, exactly as the Cosmos SDK
+/// transfer module derives it, and encodes it as a bech32 address with the given prefix (e.g.
+/// "cosmos").
+pub fn get_escrow_address(port_id: &PortId, channel_id: &ChannelId, bech32_prefix: &str) -> Signer {
+    let hash = cosmos_adr028_escrow_address(VERSION, port_id, *channel_id);
+    bech32::encode(bech32_prefix, hash)
+        .parse()
+        .expect("bech32 encoded address is never empty")
+}
+
+/// Computes the escrow accounts of all the given channels offline (see `get_escrow_address`), in
+/// the same order as the channels.
+pub fn escrow_addresses_for(
+    channels: &[(PortId, ChannelId)],
+    bech32_prefix: &str,
+) -> Vec<(ChannelId, Signer)> {
+    channels
+        .iter()
+        .map(|(port_id, channel_id)| {
+            (
+                *channel_id,
+                get_escrow_address(port_id, channel_id, bech32_prefix),
+            )
+        })
+        .collect()
+}
+
+/// Identifies the channel end whose escrow account holds the tokens of a transfer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowAddress {
+    port_id: PortId,
+    channel_id: ChannelId,
+}
+
+impl EscrowAddress {
+    pub fn new(port_id: PortId, channel_id: ChannelId) -> Self {
+        Self {
+            port_id,
+            channel_id,
+        }
+    }
+
+    /// Returns the escrow of the sending chain, i.e. the one tokens were escrowed into at send
+    /// time. Refunds (on timeout or error acknowledgement) must unescrow from this account.
+    pub fn for_source_of(packet: &Packet) -> Self {
+        Self::new(packet.source_port.clone(), packet.source_channel)
+    }
+
+    /// Returns the escrow of the receiving chain, i.e. the one tokens are unescrowed from when
+    /// they return to their source chain.
+    pub fn for_destination_of(packet: &Packet) -> Self {
+        Self::new(packet.destination_port.clone(), packet.destination_channel)
+    }
+
+    pub fn port_id(&self) -> &PortId {
+        &self.port_id
+    }
+
+    pub fn channel_id(&self) -> ChannelId {
+        self.channel_id
+    }
+}
+
 pub trait BankKeeper {
     type AccountId;
 
@@ -92,7 +324,9 @@ pub trait BankKeeper {
         amt: &PrefixedCoin,
     ) -> Result<(), Ics20Error>;
 
-    /// This function should enable burning of minted tokens in a user account
+    /// This function should enable burning of minted tokens in a user account.
+    /// Implementations must fail with `Ics20Error::insufficient_funds` (leaving the balance
+    /// untouched) if the account holds less than `amt`.
     fn burn_coins(
         &mut self,
         account: &Self::AccountId,
@@ -107,6 +341,17 @@ pub trait Ics20Context:
     + Ics20Reader<AccountId = <Self as Ics20Context>::AccountId>
 {
     type AccountId: TryFrom<Signer>;
+
+    /// Initiates the onward transfer of `coin`, which was just credited to the receiver of a packet
+    /// whose memo carried the forwarding `instruction`.
+    /// Implement only if the host chain supports packet forwarding.
+    fn forward_packet(
+        &mut self,
+        _instruction: &ForwardMemo,
+        _coin: &PrefixedCoin,
+    ) -> Result<(), Ics20Error> {
+        Ok(())
+    }
 }
 
 fn validate_transfer_channel_params(
@@ -129,15 +374,18 @@ fn validate_transfer_channel_params(
         return Err(Ics20Error::invalid_port(port_id.clone(), bound_port));
     }
 
-    if version != &Version::ics20() {
+    if version != &Version::new(ctx.version().to_string()) {
         return Err(Ics20Error::invalid_version(version.clone()));
     }
 
     Ok(())
 }
 
-fn validate_counterparty_version(counterparty_version: &Version) -> Result<(), Ics20Error> {
-    if counterparty_version == &Version::ics20() {
+fn validate_counterparty_version(
+    ctx: &impl Ics20Context,
+    counterparty_version: &Version,
+) -> Result<(), Ics20Error> {
+    if counterparty_version == &Version::new(ctx.version().to_string()) {
         Ok(())
     } else {
         Err(Ics20Error::invalid_counterparty_version(
@@ -173,18 +421,18 @@ pub fn on_chan_open_try(
     counterparty_version: &Version,
 ) -> Result<Version, Ics20Error> {
     validate_transfer_channel_params(ctx, order, port_id, channel_id, version)?;
-    validate_counterparty_version(counterparty_version)?;
-    Ok(Version::ics20())
+    validate_counterparty_version(ctx, counterparty_version)?;
+    Ok(Version::new(ctx.version().to_string()))
 }
 
 pub fn on_chan_open_ack(
-    _ctx: &mut impl Ics20Context,
+    ctx: &mut impl Ics20Context,
     _output: &mut ModuleOutputBuilder,
     _port_id: &PortId,
     _channel_id: &ChannelId,
     counterparty_version: &Version,
 ) -> Result<(), Ics20Error> {
-    validate_counterparty_version(counterparty_version)?;
+    validate_counterparty_version(ctx, counterparty_version)?;
     Ok(())
 }
 
@@ -221,24 +469,28 @@ pub fn on_recv_packet<Ctx: 'static + Ics20Context>(
     packet: &Packet,
     _relayer: &Signer,
 ) -> OnRecvPacketAck {
+    // the acknowledgement is committed in its canonical encoding
+    let encoded = |ack: Acknowledgement| Box::new(EncodedAcknowledgement::from(ack));
+
     let data = match serde_json::from_slice::<PacketData>(&packet.data) {
         Ok(data) => data,
         Err(_) => {
-            return OnRecvPacketAck::Failed(Box::new(Acknowledgement::Error(
+            return OnRecvPacketAck::Failed(encoded(Acknowledgement::Error(
                 Ics20Error::packet_data_deserialization().to_string(),
             )))
         }
     };
 
     let ack = match process_recv_packet(ctx, output, packet, data.clone()) {
-        Ok(write_fn) => OnRecvPacketAck::Successful(Box::new(Acknowledgement::success()), write_fn),
-        Err(e) => OnRecvPacketAck::Failed(Box::new(Acknowledgement::from_error(e))),
+        Ok(write_fn) => OnRecvPacketAck::Successful(encoded(Acknowledgement::success()), write_fn),
+        Err(e) => OnRecvPacketAck::Failed(encoded(Acknowledgement::from_error(e))),
     };
 
     let recv_event = RecvEvent {
         receiver: data.receiver,
         denom: data.token.denom,
         amount: data.token.amount,
+        memo: data.memo,
         success: ack.is_successful(),
     };
     output.emit(recv_event.into());
@@ -256,8 +508,7 @@ pub fn on_acknowledgement_packet(
     let data = serde_json::from_slice::<PacketData>(&packet.data)
         .map_err(|_| Ics20Error::packet_data_deserialization())?;
 
-    let acknowledgement = serde_json::from_slice::<Acknowledgement>(acknowledgement.as_ref())
-        .map_err(|_| Ics20Error::ack_deserialization())?;
+    let acknowledgement = Acknowledgement::try_from(acknowledgement.clone().into_bytes())?;
 
     process_ack_packet(ctx, packet, &data, &acknowledgement)?;
 
@@ -296,24 +547,115 @@ pub fn on_timeout_packet(
 
 #[cfg(test)]
 pub(crate) mod test {
-    use subtle_encoding::bech32;
+    use subtle_encoding::{bech32, hex};
 
-    use crate::applications::transfer::context::cosmos_adr028_escrow_address;
-    use crate::applications::transfer::error::Error as Ics20Error;
+    use crate::applications::transfer::acknowledgement::Acknowledgement;
+    use crate::applications::transfer::context::{
+        cosmos_adr028_escrow_address, escrow_addresses_for, get_escrow_address,
+        on_acknowledgement_packet, on_chan_open_try, on_recv_packet, BankKeeper, EscrowAddress,
+        Ics20Keeper, Ics20Reader,
+    };
+    use crate::applications::transfer::error::{
+        Error as Ics20Error, ErrorDetail as Ics20ErrorDetail,
+    };
+    use crate::applications::transfer::msgs::transfer::test_util::get_dummy_msg_transfer;
     use crate::applications::transfer::msgs::transfer::MsgTransfer;
+    use crate::applications::transfer::packet::PacketData;
     use crate::applications::transfer::relay::send_transfer::send_transfer;
-    use crate::applications::transfer::PrefixedCoin;
+    use crate::applications::transfer::{
+        Amount, BaseCoin, DenomTrace, PrefixedCoin, PrefixedDenom, MODULE_ID_STR, VERSION,
+    };
+    use crate::core::ics02_client::client_consensus::AnyConsensusState;
+    use crate::core::ics03_connection::connection::{
+        ConnectionEnd, Counterparty as ConnectionCounterparty, State as ConnectionState,
+    };
+    use crate::core::ics03_connection::version::get_compatible_versions;
+    use crate::core::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
+    use crate::core::ics04_channel::context::{ChannelKeeper, ChannelReader};
     use crate::core::ics04_channel::error::Error;
+    use crate::core::ics04_channel::msgs::acknowledgement::Acknowledgement as GenericAcknowledgement;
+    use crate::core::ics04_channel::packet::{Packet, Receipt, Sequence};
+    use crate::core::ics04_channel::Version;
+    use crate::core::ics05_port::context::PortReader;
+    use crate::core::ics24_host::identifier::{ChannelId, ClientId, ConnectionId, PortId};
+    use crate::core::ics26_routing::context::{ModuleId, ModuleOutputBuilder, OnRecvPacketAck};
     use crate::handler::HandlerOutputBuilder;
+    use crate::mock::client_state::MockConsensusState;
+    use crate::mock::context::MockContext;
+    use crate::mock::header::MockHeader;
     use crate::prelude::*;
-    use crate::test_utils::DummyTransferModule;
+    use crate::test_utils::{get_dummy_account_id, DummyTransferModule};
+    use crate::timestamp::{Timestamp, ZERO_DURATION};
+    use crate::Height;
+
+    /// Returns a `DummyTransferModule` with an open ICS20 channel `transfer/channel-0`, whose
+    /// counterparty is `transfer/channel-1`, ready to send packets on.
+    pub(crate) fn get_dummy_transfer_module() -> DummyTransferModule {
+        get_dummy_transfer_module_in_state(State::Open)
+    }
+
+    /// Same as `get_dummy_transfer_module`, with the channel in the given state.
+    pub(crate) fn get_dummy_transfer_module_in_state(state: State) -> DummyTransferModule {
+        let client_id = ClientId::default();
+        let connection_id = ConnectionId::default();
+        let connection_end = ConnectionEnd::new(
+            ConnectionState::Open,
+            client_id.clone(),
+            ConnectionCounterparty::new(
+                client_id.clone(),
+                Some(connection_id.clone()),
+                Default::default(),
+            ),
+            get_compatible_versions(),
+            ZERO_DURATION,
+        );
+        let channel_end = ChannelEnd::new(
+            state,
+            Order::Unordered,
+            Counterparty::new(PortId::transfer(), Some(ChannelId::new(1))),
+            vec![connection_id.clone()],
+            Version::ics20(),
+        );
+        let ctx = MockContext::default()
+            .with_client(&client_id, Height::new(0, 5))
+            .with_connection(connection_id, connection_end)
+            .with_channel(PortId::transfer(), ChannelId::new(0), channel_end)
+            .with_send_sequence(PortId::transfer(), ChannelId::new(0), 1.into());
+        DummyTransferModule::new(ctx.ibc_store_share())
+    }
+
+    /// Returns a `MsgTransfer` of `token` over the channel set up by `get_dummy_transfer_module`.
+    pub(crate) fn get_dummy_msg_transfer_of(token: PrefixedCoin) -> MsgTransfer<PrefixedCoin> {
+        MsgTransfer {
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(0),
+            token,
+            ..get_dummy_msg_transfer(10)
+        }
+    }
+
+    /// Returns a packet carrying `data` from the counterparty over the channel set up by
+    /// `get_dummy_transfer_module`.
+    pub(crate) fn get_dummy_recv_packet(data: &PacketData) -> Packet {
+        Packet {
+            sequence: 1.into(),
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(1),
+            destination_port: PortId::transfer(),
+            destination_channel: ChannelId::new(0),
+            data: serde_json::to_vec(data).unwrap(),
+            ..Packet::default()
+        }
+    }
 
     pub(crate) fn deliver(
         ctx: &mut DummyTransferModule,
         output: &mut HandlerOutputBuilder<()>,
         msg: MsgTransfer<PrefixedCoin>,
     ) -> Result<(), Error> {
-        send_transfer(ctx, output, msg).map_err(|e: Ics20Error| Error::app_module(e.to_string()))
+        send_transfer(ctx, output, msg)
+            .map(|_| ())
+            .map_err(|e: Ics20Error| Error::app_module(e.to_string()))
     }
 
     #[test]
@@ -322,7 +664,7 @@ pub(crate) mod test {
             let port_id = port_id.parse().unwrap();
             let channel_id = channel_id.parse().unwrap();
             let gen_address = {
-                let addr = cosmos_adr028_escrow_address(&port_id, channel_id);
+                let addr = cosmos_adr028_escrow_address(VERSION, &port_id, channel_id);
                 bech32::encode("cosmos", addr)
             };
             assert_eq!(gen_address, address.to_owned())
@@ -345,4 +687,443 @@ pub(crate) mod test {
             "cosmos177x69sver58mcfs74x6dg0tv6ls4s3xmmcaw53",
         );
     }
+
+    #[test]
+    fn test_get_escrow_address() {
+        // obtained using `gaiad query ibc-transfer escrow-address transfer channel-141`
+        assert_eq!(
+            get_escrow_address(&PortId::transfer(), &ChannelId::new(141), "cosmos").as_ref(),
+            "cosmos1x54ltnyg88k0ejmk8ytwrhd3ltm84xehrnlslf"
+        );
+    }
+
+    #[test]
+    fn test_escrow_addresses_for() {
+        let channels: Vec<_> = [141, 207, 187]
+            .into_iter()
+            .map(|n| (PortId::transfer(), ChannelId::new(n)))
+            .collect();
+
+        let addresses: Vec<_> = escrow_addresses_for(&channels, "cosmos")
+            .into_iter()
+            .map(|(channel_id, address)| (channel_id, address.to_string()))
+            .collect();
+
+        // obtained using `gaiad query ibc-transfer escrow-address transfer [channel-id]`
+        assert_eq!(
+            addresses,
+            vec![
+                (
+                    ChannelId::new(141),
+                    "cosmos1x54ltnyg88k0ejmk8ytwrhd3ltm84xehrnlslf".to_string()
+                ),
+                (
+                    ChannelId::new(207),
+                    "cosmos1ju6tlfclulxumtt2kglvnxduj5d93a64r5czge".to_string()
+                ),
+                (
+                    ChannelId::new(187),
+                    "cosmos177x69sver58mcfs74x6dg0tv6ls4s3xmmcaw53".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refund_escrow_differs_from_recv_escrow() {
+        let packet = Packet {
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(0),
+            destination_port: PortId::transfer(),
+            destination_channel: ChannelId::new(1),
+            ..Packet::default()
+        };
+
+        let refund_escrow = EscrowAddress::for_source_of(&packet);
+        let recv_escrow = EscrowAddress::for_destination_of(&packet);
+        assert_eq!(
+            refund_escrow,
+            EscrowAddress::new(PortId::transfer(), ChannelId::new(0))
+        );
+        assert_ne!(refund_escrow, recv_escrow);
+        assert_ne!(
+            cosmos_adr028_escrow_address(
+                VERSION,
+                refund_escrow.port_id(),
+                refund_escrow.channel_id()
+            ),
+            cosmos_adr028_escrow_address(VERSION, recv_escrow.port_id(), recv_escrow.channel_id())
+        );
+    }
+
+    #[test]
+    fn test_channel_escrow_balances() {
+        let coin = |denom: &str, amount: u64| -> PrefixedCoin {
+            BaseCoin {
+                denom: denom.parse().unwrap(),
+                amount: amount.into(),
+            }
+            .into()
+        };
+
+        let sender = get_dummy_account_id();
+        let mut ctx = DummyTransferModule::new(MockContext::default().ibc_store_share())
+            .with_balance(sender.clone(), coin("uatom", 100))
+            .with_balance(sender.clone(), coin("uosmo", 100));
+
+        let port_id = PortId::transfer();
+        let channel_id = ChannelId::new(0);
+        let escrow_address = ctx
+            .get_channel_escrow_address(&port_id, channel_id)
+            .unwrap();
+
+        assert_eq!(
+            ctx.channel_escrow_balances(&port_id, channel_id),
+            Some(vec![])
+        );
+
+        ctx.send_coins(&sender, &escrow_address, &coin("uatom", 10))
+            .unwrap();
+        ctx.send_coins(&sender, &escrow_address, &coin("uosmo", 20))
+            .unwrap();
+        ctx.send_coins(&sender, &escrow_address, &coin("uatom", 5))
+            .unwrap();
+
+        assert_eq!(
+            ctx.channel_escrow_balances(&port_id, channel_id),
+            Some(vec![coin("uatom", 15), coin("uosmo", 20)])
+        );
+        assert_eq!(
+            ctx.channel_escrow_balances(&port_id, ChannelId::new(1)),
+            Some(vec![])
+        );
+
+        let denom = |denom: &str| -> PrefixedDenom { denom.parse().unwrap() };
+        assert_eq!(
+            ctx.channel_escrow_balance(&port_id, channel_id, &denom("uatom")),
+            Some(15u64.into())
+        );
+        assert_eq!(
+            ctx.channel_escrow_balance(&port_id, channel_id, &denom("ujuno")),
+            Some(Amount::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_next_sequences() {
+        let mut ctx = get_dummy_transfer_module();
+        let port_channel_id = (PortId::transfer(), ChannelId::new(0));
+
+        assert!(ctx.get_next_sequence_recv(&port_channel_id).is_err());
+        assert!(ctx.get_next_sequence_ack(&port_channel_id).is_err());
+
+        ctx.store_next_sequence_send(port_channel_id.clone(), 3.into())
+            .unwrap();
+        ctx.store_next_sequence_recv(port_channel_id.clone(), 5.into())
+            .unwrap();
+        ctx.store_next_sequence_ack(port_channel_id.clone(), 7.into())
+            .unwrap();
+
+        assert_eq!(
+            ctx.get_next_sequence_send(&port_channel_id).unwrap(),
+            Sequence::from(3)
+        );
+        assert_eq!(
+            ctx.get_next_sequence_recv(&port_channel_id).unwrap(),
+            Sequence::from(5)
+        );
+        assert_eq!(
+            ctx.get_next_sequence_ack(&port_channel_id).unwrap(),
+            Sequence::from(7)
+        );
+    }
+
+    #[test]
+    fn test_store_channel() {
+        let mut ctx = get_dummy_transfer_module();
+        let port_channel_id = (PortId::transfer(), ChannelId::new(3));
+        let channel_end = ChannelEnd::new(
+            State::Init,
+            Order::Unordered,
+            Counterparty::new(PortId::transfer(), None),
+            vec![ConnectionId::default()],
+            Version::ics20(),
+        );
+
+        assert!(ctx.channel_end(&port_channel_id).is_err());
+
+        ctx.store_channel(port_channel_id.clone(), &channel_end)
+            .unwrap();
+        assert_eq!(ctx.channel_end(&port_channel_id).unwrap(), channel_end);
+    }
+
+    #[test]
+    fn test_delete_packet_commitment() {
+        let mut ctx = get_dummy_transfer_module();
+        let key = (PortId::transfer(), ChannelId::new(0), Sequence::from(1));
+        let commitment =
+            ctx.packet_commitment(vec![1, 2, 3], Height::new(0, 10), Timestamp::none());
+
+        ctx.store_packet_commitment(key.clone(), commitment.clone())
+            .unwrap();
+        assert_eq!(ctx.get_packet_commitment(&key).unwrap(), commitment);
+
+        ctx.delete_packet_commitment(key.clone()).unwrap();
+        assert!(ctx.get_packet_commitment(&key).is_err());
+
+        // as in the spec, deleting an absent commitment is a no-op
+        ctx.delete_packet_commitment(key).unwrap();
+    }
+
+    #[test]
+    fn test_lookup_module_by_port() {
+        let module_id = ModuleId::new(MODULE_ID_STR.into()).unwrap();
+        let mut mock_ctx = MockContext::default();
+        mock_ctx.scope_port_to_module(PortId::transfer(), module_id.clone());
+        let ctx = DummyTransferModule::new(mock_ctx.ibc_store_share());
+
+        assert_eq!(
+            ctx.lookup_module_by_port(&PortId::transfer()).unwrap(),
+            module_id
+        );
+        assert!(ctx
+            .lookup_module_by_port(&"unbound".parse().unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_host_height_and_consensus_state() {
+        let ctx = get_dummy_transfer_module();
+        assert_eq!(ctx.host_height(), Height::zero());
+        assert!(ctx.pending_host_consensus_state().is_err());
+
+        let host_height = Height::new(0, 10);
+        let host_timestamp = Timestamp::from_nanoseconds(1_000_000_000).unwrap();
+        let consensus_state: AnyConsensusState =
+            MockConsensusState::new(MockHeader::new(host_height).with_timestamp(host_timestamp))
+                .into();
+        let ctx = get_dummy_transfer_module()
+            .with_host_height(host_height)
+            .with_host_consensus_state(host_height, consensus_state.clone());
+
+        assert_eq!(ctx.host_height(), host_height);
+        assert_eq!(
+            ctx.host_consensus_state(host_height).unwrap(),
+            consensus_state
+        );
+        assert!(ctx.host_consensus_state(Height::new(0, 9)).is_err());
+        assert_eq!(ctx.pending_host_consensus_state().unwrap(), consensus_state);
+        assert_eq!(ctx.host_timestamp(), host_timestamp);
+    }
+
+    #[test]
+    fn test_packet_receipts() {
+        let mut ctx = get_dummy_transfer_module();
+        let key = (PortId::transfer(), ChannelId::new(0), Sequence::from(1));
+
+        assert!(ctx.get_packet_receipt(&key).is_err());
+
+        ctx.store_packet_receipt(key.clone(), Receipt::Ok).unwrap();
+        assert!(matches!(ctx.get_packet_receipt(&key), Ok(Receipt::Ok)));
+
+        let other_key = (PortId::transfer(), ChannelId::new(0), Sequence::from(2));
+        assert!(ctx.get_packet_receipt(&other_key).is_err());
+    }
+
+    #[test]
+    fn test_packet_acknowledgements() {
+        let mut ctx = get_dummy_transfer_module();
+        let key = (PortId::transfer(), ChannelId::new(0), Sequence::from(1));
+        let ack_commitment = ctx.ack_commitment(Acknowledgement::success_bytes().into());
+
+        assert!(ctx.get_packet_acknowledgement(&key).is_err());
+
+        ctx.store_packet_acknowledgement(key.clone(), ack_commitment.clone())
+            .unwrap();
+        assert_eq!(
+            ctx.get_packet_acknowledgement(&key).unwrap(),
+            ack_commitment
+        );
+
+        ctx.delete_packet_acknowledgement(key.clone()).unwrap();
+        assert!(ctx.get_packet_acknowledgement(&key).is_err());
+    }
+
+    #[test]
+    fn test_export_import_denom_traces() {
+        let traces: Vec<DenomTrace> = vec![
+            "transfer/channel-0/uatom".parse().unwrap(),
+            "transfer/channel-1/transfer/channel-5/uosmo"
+                .parse()
+                .unwrap(),
+        ];
+
+        let mut ctx = get_dummy_transfer_module();
+        for trace in &traces {
+            ctx.set_denom_trace(trace).unwrap();
+        }
+        let exported = ctx.export_denom_traces();
+        assert_eq!(exported.len(), 2);
+
+        let mut imported_ctx = get_dummy_transfer_module();
+        imported_ctx.import_denom_traces(exported.clone()).unwrap();
+        assert_eq!(imported_ctx.export_denom_traces(), exported);
+        for trace in traces {
+            assert_eq!(imported_ctx.get_denom_trace(&trace.hashed()), Some(trace));
+        }
+    }
+
+    #[test]
+    fn test_import_mismatched_denom_trace() {
+        let trace: DenomTrace = "transfer/channel-0/uatom".parse().unwrap();
+        let other: DenomTrace = "transfer/channel-1/uatom".parse().unwrap();
+
+        // `trace` paired with the hash of `other`, after a valid entry
+        let mut ctx = get_dummy_transfer_module();
+        let err = ctx
+            .import_denom_traces(vec![
+                (other.hashed(), other.clone()),
+                (other.hashed(), trace.clone()),
+            ])
+            .unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            Ics20ErrorDetail::MismatchedDenomTraceHash(e) if e.hash == other.hashed() && e.denom == trace
+        ));
+
+        // no entry is stored
+        assert_eq!(ctx.get_denom_trace(&other.hashed()), None);
+        assert_eq!(ctx.get_denom_trace(&trace.hashed()), None);
+        assert!(ctx.export_denom_traces().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_denom() {
+        let trace: DenomTrace = "transfer/channel-0/uatom".parse().unwrap();
+        let mut ctx = get_dummy_transfer_module();
+        ctx.set_denom_trace(&trace).unwrap();
+
+        let hashed = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        assert_eq!(ctx.resolve_denom(hashed), Some(trace.clone()));
+        assert_eq!(
+            ctx.resolve_denom("transfer/channel-0/uatom"),
+            Some(trace.clone())
+        );
+        assert_eq!(ctx.resolve_denom("uatom"), Some("uatom".parse().unwrap()));
+
+        // unknown hash
+        let unknown = "ibc/C4CFF46FD6DE35CA4CF4CE031E643C8FDC9BA4B99AE598E9B0ED98FE3A2319F9";
+        assert_eq!(ctx.resolve_denom(unknown), None);
+        assert_eq!(ctx.resolve_denom("ibc/nothex"), None);
+    }
+
+    #[test]
+    fn test_version() {
+        let open_try = |ctx: &mut DummyTransferModule, version: &str| {
+            let version = Version::new(version.to_string());
+            on_chan_open_try(
+                ctx,
+                &mut ModuleOutputBuilder::new(),
+                Order::Unordered,
+                &[ConnectionId::default()],
+                &PortId::transfer(),
+                &ChannelId::new(0),
+                &Counterparty::new(PortId::transfer(), Some(ChannelId::new(1))),
+                &version,
+                &version,
+            )
+        };
+        let port_id = PortId::transfer();
+        let channel_id = ChannelId::new(141);
+
+        let mut ctx = get_dummy_transfer_module();
+        assert_eq!(ctx.version(), VERSION);
+        assert_eq!(open_try(&mut ctx, VERSION).unwrap(), Version::ics20());
+        // obtained using `gaiad query ibc-transfer escrow-address transfer channel-141`
+        assert_eq!(
+            ctx.get_channel_escrow_address(&port_id, channel_id)
+                .unwrap()
+                .to_string(),
+            bech32::decode("cosmos1x54ltnyg88k0ejmk8ytwrhd3ltm84xehrnlslf")
+                .map(|(_, hash)| String::from_utf8(hex::encode_upper(hash)).unwrap())
+                .unwrap()
+        );
+
+        let mut ctx = get_dummy_transfer_module().with_version("ics20-custom");
+        assert_eq!(ctx.version(), "ics20-custom");
+        assert_eq!(
+            open_try(&mut ctx, "ics20-custom").unwrap(),
+            Version::new("ics20-custom".to_string())
+        );
+        assert!(open_try(&mut ctx, VERSION).is_err());
+        assert_eq!(
+            ctx.get_channel_escrow_address(&port_id, channel_id)
+                .unwrap()
+                .to_string(),
+            String::from_utf8(hex::encode_upper(cosmos_adr028_escrow_address(
+                "ics20-custom",
+                &port_id,
+                channel_id
+            )))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_recv_ack_round_trip() {
+        let data = PacketData {
+            token: PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            sender: get_dummy_account_id(),
+            receiver: get_dummy_account_id(),
+            memo: Default::default(),
+        };
+        let packet = get_dummy_recv_packet(&data);
+        let relayer = get_dummy_account_id();
+
+        // the receiving chain commits the bytes exposed by the acknowledgement trait object
+        let receiving_ctx = get_dummy_transfer_module();
+        let ack = match on_recv_packet(
+            &receiving_ctx,
+            &mut ModuleOutputBuilder::new(),
+            &packet,
+            &relayer,
+        ) {
+            OnRecvPacketAck::Successful(ack, _) => ack,
+            _ => panic!("expected a successful acknowledgement"),
+        };
+        let bytes = (*ack).as_ref().to_vec();
+        assert_eq!(bytes, Acknowledgement::success_bytes());
+
+        // which the sending chain accepts
+        let mut sending_ctx = get_dummy_transfer_module();
+        on_acknowledgement_packet(
+            &mut sending_ctx,
+            &mut ModuleOutputBuilder::new(),
+            &packet,
+            &GenericAcknowledgement::from(bytes),
+            &relayer,
+        )
+        .unwrap();
+
+        let invalid = Packet {
+            data: b"not ics20".to_vec(),
+            ..packet
+        };
+        let ack = match on_recv_packet(
+            &receiving_ctx,
+            &mut ModuleOutputBuilder::new(),
+            &invalid,
+            &relayer,
+        ) {
+            OnRecvPacketAck::Failed(ack) => ack,
+            _ => panic!("expected a failed acknowledgement"),
+        };
+        assert!(matches!(
+            Acknowledgement::try_from((*ack).as_ref().to_vec()).unwrap(),
+            Acknowledgement::Error(_)
+        ));
+    }
 }
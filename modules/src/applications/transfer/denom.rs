@@ -1,10 +1,14 @@
+use alloc::collections::btree_map::BTreeMap;
+use alloc::collections::btree_set::BTreeSet;
 use core::fmt;
+use core::ops::Add;
 use core::str::FromStr;
 
 use derive_more::{Display, From, Into};
 use ibc_proto::cosmos::base::v1beta1::Coin as RawCoin;
 use ibc_proto::ibc::applications::transfer::v1::DenomTrace as RawDenomTrace;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use super::error::Error;
 use crate::bigint::U256;
@@ -12,6 +16,10 @@ use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::prelude::*;
 use crate::serializers::serde_string;
 
+/// The maximum number of hops (i.e. `{port-id}/{channel-id}` pairs) a `TracePath` may contain.
+/// Longer paths are rejected during parsing.
+pub const MAX_TRACE_DEPTH: usize = 64;
+
 /// A `Coin` type with fully qualified `PrefixedDenom`.
 pub type PrefixedCoin = Coin<PrefixedDenom>;
 
@@ -19,23 +27,63 @@ pub type PrefixedCoin = Coin<PrefixedDenom>;
 pub type BaseCoin = Coin<BaseDenom>;
 
 /// Base denomination type
-#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Display)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize, Display)]
 #[serde(transparent)]
 pub struct BaseDenom(String);
 
+impl BaseDenom {
+    /// Returns true if this denomination matches `other` when ignoring ASCII case, e.g. "uatom"
+    /// and "UATOM". This is an opt-in helper for matching against e.g. allow lists: denominations
+    /// are case-sensitive in the Cosmos SDK, and so is the `PartialEq` implementation.
+    pub fn eq_ignore_ascii_case(&self, other: &str) -> bool {
+        self.0.eq_ignore_ascii_case(other)
+    }
+}
+
+/// Validates a base denomination against the Cosmos SDK coin denom regex, i.e.
+/// `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`.
+fn validate_base_denom(denom: &str) -> Result<(), Error> {
+    let invalid = |reason: &str| Error::invalid_base_denom(denom.to_string(), reason.to_string());
+
+    if !(3..=128).contains(&denom.len()) {
+        return Err(invalid("length must be between 3 and 128 characters"));
+    }
+    if !denom.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(invalid("must start with a letter"));
+    }
+    if let Some(c) = denom
+        .chars()
+        .find(|&c| !c.is_ascii_alphanumeric() && !"/:._-".contains(c))
+    {
+        return Err(invalid(&format!("invalid character {:?}", c)));
+    }
+    if denom.split('/').any(str::is_empty) {
+        return Err(invalid("must not contain empty segments"));
+    }
+
+    Ok(())
+}
+
 impl FromStr for BaseDenom {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.trim().is_empty() {
-            Err(Error::empty_base_denom())
-        } else {
-            Ok(BaseDenom(s.to_owned()))
+            return Err(Error::empty_base_denom());
+        }
+        validate_base_denom(s)?;
+        // e.g. "transfer/channel-0", which is displayed the same as a trace path
+        if s.split('/')
+            .nth(1)
+            .map_or(false, |segment| ChannelId::from_str(segment).is_ok())
+        {
+            return Err(Error::ambiguous_base_denom(s.to_string()));
         }
+        Ok(BaseDenom(s.to_owned()))
     }
 }
 
-#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct TracePrefix {
     port_id: PortId,
     channel_id: ChannelId,
@@ -60,15 +108,29 @@ impl fmt::Display for TracePrefix {
 // Internally, the `TracePath` is modelled as a `Vec<TracePrefix>` but with the order reversed, i.e.
 // "transfer/channel-0/transfer/channel-1/uatom" => `["transfer/channel-1", "transfer/channel-0"]`
 // This is done for ease of addition/removal of prefixes.
-#[derive(Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord, From)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord, From)]
 pub struct TracePath(Vec<TracePrefix>);
 
 impl TracePath {
+    /// Creates a path from its prefixes in display order, i.e. starting with the outermost prefix
+    /// (the most recent hop) and ending with the one closest to the source of the token, e.g.
+    /// `["transfer/channel-0", "transfer/channel-1"]` => "transfer/channel-0/transfer/channel-1".
+    /// Note that `From<Vec<TracePrefix>>` instead expects the prefixes in reverse display order.
+    pub fn from_prefixes(prefixes: Vec<TracePrefix>) -> Self {
+        prefixes.into_iter().collect()
+    }
+
     /// Returns true iff this path starts with the specified prefix
     pub fn starts_with(&self, prefix: &TracePrefix) -> bool {
         self.0.last().map(|p| p == prefix).unwrap_or(false)
     }
 
+    /// Returns true iff the specified prefix is any of the hops of this path, unlike `starts_with`
+    /// which only checks the outermost one.
+    pub fn contains(&self, prefix: &TracePrefix) -> bool {
+        self.0.contains(prefix)
+    }
+
     /// Removes the specified prefix from the path if there is a match, otherwise does nothing.
     pub fn remove_prefix(&mut self, prefix: &TracePrefix) {
         if self.starts_with(prefix) {
@@ -76,15 +138,94 @@ impl TracePath {
         }
     }
 
+    /// Returns a copy of the path with the specified prefix removed, or `None` if the path does not
+    /// start with it. This is the non-mutating analogue of `remove_prefix`.
+    pub fn strip_prefix(&self, prefix: &TracePrefix) -> Option<TracePath> {
+        if self.starts_with(prefix) {
+            let mut stripped = self.clone();
+            stripped.0.pop();
+            Some(stripped)
+        } else {
+            None
+        }
+    }
+
     /// Adds the specified prefix to the path.
     pub fn add_prefix(&mut self, prefix: TracePrefix) {
         self.0.push(prefix)
     }
 
+    /// Appends the hops of `other` to this path, i.e. `a.extend(b)` results in the path of a token
+    /// that already carried path `a` after being routed through the additional hops `b`. The hops
+    /// of `b` are thus the most recent ones and are displayed first, e.g. extending
+    /// "transfer/channel-0" with "transfer/channel-1" yields "transfer/channel-1/transfer/channel-0".
+    pub fn extend(&mut self, other: TracePath) {
+        self.0.extend(other.0)
+    }
+
     /// Returns true if the path is empty and false otherwise.
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Returns the number of prefixes (i.e. hops) in the path.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns an iterator over the prefixes of the path in the same order as they are displayed,
+    /// i.e. from the most recent hop to the hop closest to the source chain, e.g.
+    /// "transfer/channel-0/transfer/channel-1" yields "transfer/channel-0" first.
+    pub fn iter(&self) -> impl Iterator<Item = &TracePrefix> {
+        self.0.iter().rev()
+    }
+
+    /// Returns the origin-side prefix of the path, i.e. the hop closest to the source chain, which
+    /// is displayed *last*, e.g. "transfer/channel-1" for "transfer/channel-0/transfer/channel-1".
+    pub fn first_prefix(&self) -> Option<&TracePrefix> {
+        // prefixes are stored in reverse display order
+        self.0.first()
+    }
+
+    /// Returns the most recently added prefix of the path, i.e. the immediate previous hop, which
+    /// is displayed *first*, e.g. "transfer/channel-0" for "transfer/channel-0/transfer/channel-1".
+    pub fn last_prefix(&self) -> Option<&TracePrefix> {
+        self.0.last()
+    }
+
+    /// Returns the port and channel identifiers making up the path as separate segments, in the
+    /// same order as they are displayed, e.g. "transfer/channel-0/transfer/channel-1" yields
+    /// `["transfer", "channel-0", "transfer", "channel-1"]`.
+    pub fn to_segments(&self) -> Vec<String> {
+        self.iter()
+            .flat_map(|prefix| [prefix.port_id.to_string(), prefix.channel_id.to_string()])
+            .collect()
+    }
+
+    /// Returns true iff every prefix (i.e. hop) of the path is in the `known` set. This is
+    /// trivially the case for an empty path.
+    pub fn all_known(&self, known: &BTreeSet<TracePrefix>) -> bool {
+        self.0.iter().all(|prefix| known.contains(prefix))
+    }
+}
+
+/// Collects prefixes given in display order into a path, see `TracePath::from_prefixes`.
+impl FromIterator<TracePrefix> for TracePath {
+    fn from_iter<I: IntoIterator<Item = TracePrefix>>(prefixes: I) -> Self {
+        let mut prefixes: Vec<TracePrefix> = prefixes.into_iter().collect();
+        prefixes.reverse();
+        Self(prefixes)
+    }
+}
+
+/// Concatenates two paths, see `TracePath::extend`.
+impl Add for TracePath {
+    type Output = TracePath;
+
+    fn add(mut self, rhs: TracePath) -> Self::Output {
+        self.extend(rhs);
+        self
+    }
 }
 
 impl<'a> TryFrom<Vec<&'a str>> for TracePath {
@@ -95,23 +236,83 @@ impl<'a> TryFrom<Vec<&'a str>> for TracePath {
             return Err(Error::invalid_trace_length(v.len()));
         }
 
+        let depth = v.len() / 2;
+        if depth > MAX_TRACE_DEPTH {
+            return Err(Error::trace_path_too_long(depth, MAX_TRACE_DEPTH));
+        }
+
         let mut trace = vec![];
         let id_pairs = v.chunks_exact(2).map(|paths| (paths[0], paths[1]));
         for (pos, (port_id, channel_id)) in id_pairs.rev().enumerate() {
-            let port_id =
-                PortId::from_str(port_id).map_err(|e| Error::invalid_trace_port_id(pos, e))?;
-            let channel_id = ChannelId::from_str(channel_id)
-                .map_err(|e| Error::invalid_trace_channel_id(pos, e))?;
-            trace.push(TracePrefix {
-                port_id,
-                channel_id,
-            });
+            trace.push(parse_trace_prefix(pos, port_id, channel_id)?);
         }
 
         Ok(trace.into())
     }
 }
 
+/// Parses the prefix at position `pos` of a trace path, counting from the innermost prefix.
+fn parse_trace_prefix(pos: usize, port_id: &str, channel_id: &str) -> Result<TracePrefix, Error> {
+    let port_id = PortId::from_str(port_id)
+        .map_err(|e| Error::invalid_trace_port_id(pos, port_id.to_string(), e))?;
+    let channel_id = ChannelId::from_str(channel_id)
+        .map_err(|e| Error::invalid_trace_channel_id(pos, channel_id.to_string(), e))?;
+    Ok(TracePrefix {
+        port_id,
+        channel_id,
+    })
+}
+
+impl TracePath {
+    /// Parses a trace path exactly like `FromStr`, with the same validation and errors, but in a
+    /// single pass over `s` and without first collecting its segments.
+    pub fn parse_streaming(s: &str) -> Result<Self, Error> {
+        let mut segments = s.split('/');
+        let mut len = 0;
+        // the prefixes in display order
+        let mut prefixes = vec![];
+        // `FromStr` reports the invalid prefix closest to the end of `s`, along with its position
+        // counting from the end, which is only known once `s` has been walked through
+        let mut last_invalid = None;
+
+        while let Some(port_id) = segments.next() {
+            len += 1;
+            let channel_id = match segments.next() {
+                Some(channel_id) => channel_id,
+                None => break,
+            };
+            len += 1;
+
+            if len / 2 > MAX_TRACE_DEPTH {
+                // the path is too long anyway, so only count the remaining segments
+                continue;
+            }
+            match parse_trace_prefix(0, port_id, channel_id) {
+                Ok(prefix) => prefixes.push(prefix),
+                Err(_) => last_invalid = Some((len / 2 - 1, port_id, channel_id)),
+            }
+        }
+
+        if len == 1 && s.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        if len % 2 != 0 {
+            return Err(Error::invalid_trace_length(len));
+        }
+        let depth = len / 2;
+        if depth > MAX_TRACE_DEPTH {
+            return Err(Error::trace_path_too_long(depth, MAX_TRACE_DEPTH));
+        }
+        if let Some((index, port_id, channel_id)) = last_invalid {
+            // fails again, now with the position of the prefix
+            parse_trace_prefix(depth - 1 - index, port_id, channel_id)?;
+        }
+
+        prefixes.reverse();
+        Ok(Self(prefixes))
+    }
+}
+
 impl FromStr for TracePath {
     type Err = Error;
 
@@ -142,7 +343,10 @@ impl fmt::Display for TracePath {
 }
 
 /// A type that contains the base denomination for ICS20 and the source tracing information path.
-#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+///
+/// A `TracePath` without prefixes has a single representation, however it is constructed, so any
+/// two denominations without hops and with the same base denomination are equal and hash equally.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct PrefixedDenom {
     /// A series of `{port-id}/{channel-id}`s for tracing the source of the token.
     #[serde(with = "serde_string")]
@@ -151,7 +355,77 @@ pub struct PrefixedDenom {
     base_denom: BaseDenom,
 }
 
+/// The denominations of a token on both sides of a transfer, as returned by
+/// `PrefixedDenom::transfer_preview`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferPreview {
+    /// The denomination of the token on the sending chain.
+    pub source_denom: PrefixedDenom,
+    /// The `ibc/{HASH}` denomination of the voucher minted on the receiving chain.
+    pub destination_denom: String,
+}
+
 impl PrefixedDenom {
+    /// Leniently parses a denomination produced by sloppy tooling, by collapsing runs of
+    /// consecutive slashes into a single one before parsing, e.g. "transfer//channel-0//uatom" is
+    /// parsed as "transfer/channel-0/uatom".
+    /// Only use this to import untrusted data, `from_str` must be used everywhere else as it
+    /// rejects such denominations.
+    pub fn from_str_lenient(s: &str) -> Result<Self, Error> {
+        let mut collapsed = String::with_capacity(s.len());
+        for c in s.chars() {
+            if c != '/' || !collapsed.ends_with('/') {
+                collapsed.push(c);
+            }
+        }
+        Self::from_str(&collapsed)
+    }
+
+    /// Parses a full denomination trace, e.g. "transfer/channel-0/uatom", into its trace path and
+    /// base denomination, unlike `TryFrom<RawDenomTrace>` which takes them separately.
+    /// This is the same as `from_str`.
+    pub fn from_full_denom(denom: &str) -> Result<DenomTrace, Error> {
+        Self::from_str(denom)
+    }
+
+    /// Returns true if this trace hashes to the given `ibc/{HASH}` denomination, i.e. if it is the
+    /// trace of the vouchers of that denomination.
+    pub fn matches_ibc_denom(&self, ibc_denom: &IbcDenomHash) -> bool {
+        verify_trace_entry(ibc_denom.hashed_denom(), self)
+    }
+
+    /// Returns the trace path of this denomination.
+    pub fn trace_path(&self) -> &TracePath {
+        &self.trace_path
+    }
+
+    /// Returns the base denomination of this denomination.
+    pub fn base_denom(&self) -> &BaseDenom {
+        &self.base_denom
+    }
+
+    /// Returns true if the denomination is native to the current chain, i.e. its trace path is
+    /// empty, and false if it is a voucher.
+    pub fn is_native(&self) -> bool {
+        self.trace_path.is_empty()
+    }
+
+    /// Returns the SHA-256 hash of the full denomination (i.e. `{trace_path}/{base_denom}`).
+    /// See `hash_denom`.
+    pub fn hashed(&self) -> HashedDenom {
+        hash_denom(self)
+    }
+
+    /// Returns true if the base denomination is itself a hashed IBC denomination (i.e. of the form
+    /// `ibc/{64 hex characters}`), which indicates a voucher that was not resolved to its full trace
+    /// before being sent.
+    pub fn has_hashed_base(&self) -> bool {
+        match self.base_denom.0.strip_prefix("ibc/") {
+            Some(hash) => hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()),
+            None => false,
+        }
+    }
+
     /// Removes the specified prefix from the trace path if there is a match, otherwise does nothing.
     pub fn remove_trace_prefix(&mut self, prefix: &TracePrefix) {
         self.trace_path.remove_prefix(prefix)
@@ -161,6 +435,121 @@ impl PrefixedDenom {
     pub fn add_trace_prefix(&mut self, prefix: TracePrefix) {
         self.trace_path.add_prefix(prefix)
     }
+
+    /// Previews a transfer of this denomination to the chain whose end of the channel is
+    /// `dest_port`/`dest_channel`, returning this denomination along with the hashed denomination
+    /// of the voucher the receiving chain mints for it.
+    /// The token is assumed to move away from its source, i.e. this does not apply to vouchers
+    /// returning over the channel they came from, which are unescrowed instead.
+    pub fn transfer_preview(&self, dest_port: &PortId, dest_channel: ChannelId) -> TransferPreview {
+        let mut voucher = self.clone();
+        voucher.add_trace_prefix(TracePrefix::new(dest_port.clone(), dest_channel));
+        TransferPreview {
+            source_denom: self.clone(),
+            destination_denom: voucher.hashed().to_ibc_denom(),
+        }
+    }
+
+    /// Returns the outermost prefix of the trace path, i.e. the port and channel over which the
+    /// token must be sent to return it one hop closer to its source, or `None` for native denoms.
+    pub fn return_route(&self) -> Option<TracePrefix> {
+        self.trace_path.0.last().cloned()
+    }
+
+    /// Returns the hops this token took after having first travelled along `base_route`, or
+    /// `None` if its trace path does not originate with `base_route`. The hops are returned in
+    /// display order, e.g. for "transfer/channel-0/transfer/channel-1/uatom" over the base route
+    /// "transfer/channel-1" the result is `["transfer/channel-0"]`.
+    pub fn extra_hops_over(&self, base_route: &TracePath) -> Option<Vec<TracePrefix>> {
+        self.trace_path
+            .0
+            .strip_prefix(base_route.0.as_slice())
+            .map(|extra_hops| extra_hops.iter().rev().cloned().collect())
+    }
+
+    /// Returns the minimal trace path of this token for display purposes, see `minimal_path_with`.
+    ///
+    /// A trace path alone cannot tell a pass-through hop from a real one, since channel IDs are
+    /// only unique per chain (e.g. "transfer/channel-0/transfer/channel-0/uatom" went through
+    /// three different chains), so to be conservative no hop is treated as a pass-through and the
+    /// trace path is returned unchanged.
+    pub fn minimal_path(&self) -> TracePath {
+        self.minimal_path_with(&BTreeSet::new())
+    }
+
+    /// Returns a copy of the trace path without the given pass-through hops, intended for display
+    /// purposes only (the result must never be used to route or escrow tokens).
+    ///
+    /// Only the hops in `pass_throughs` are dropped. The innermost hop is always kept as it is the
+    /// end, on the first receiving chain, of the channel over which the token left its source
+    /// chain, e.g.
+    /// "transfer/channel-0/transfer/channel-5/transfer/channel-1" without the pass-through
+    /// "transfer/channel-5" is "transfer/channel-0/transfer/channel-1".
+    pub fn minimal_path_with(&self, pass_throughs: &BTreeSet<TracePrefix>) -> TracePath {
+        let minimal = self
+            .trace_path
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(i, prefix)| *i == 0 || !pass_throughs.contains(prefix))
+            .map(|(_, prefix)| prefix.clone())
+            .collect();
+        TracePath(minimal)
+    }
+
+    /// Returns a copy of this denomination with each prefix of its trace path replaced by its
+    /// canonical equivalent in `equivalences`, if any. This is meant for chains that receive the
+    /// same token over redundant equivalent channels and want to account for it under a single path.
+    pub fn canonicalize_via(&self, equivalences: &BTreeMap<TracePrefix, TracePrefix>) -> Self {
+        let trace_path = self
+            .trace_path
+            .0
+            .iter()
+            .map(|prefix| equivalences.get(prefix).unwrap_or(prefix).clone())
+            .collect::<Vec<_>>()
+            .into();
+
+        Self {
+            trace_path,
+            base_denom: self.base_denom.clone(),
+        }
+    }
+
+    /// Returns which chain of a transfer is the source of this denomination, given the `prefix`
+    /// made up of the packet's source port and channel (see `is_sender_chain_source` for what
+    /// being the "source" means).
+    ///
+    /// On the receiving chain, `Source::Receiver` means the tokens are unescrowed (after removing
+    /// `prefix` from the denomination), while `Source::Sender` means vouchers are minted (after
+    /// adding the packet's destination port and channel as a prefix).
+    pub fn source_chain(&self, prefix: &TracePrefix) -> Source {
+        if self.trace_path.starts_with(prefix) {
+            Source::Receiver
+        } else {
+            Source::Sender
+        }
+    }
+
+    /// Returns true if both denominations are held under the same denomination on-chain, i.e.
+    /// their balances would be merged. Vouchers are held under their `ibc/{hash}` denomination,
+    /// while native tokens are held under their base denomination.
+    pub fn same_onchain_denom(&self, other: &PrefixedDenom) -> bool {
+        match (self.trace_path.is_empty(), other.trace_path.is_empty()) {
+            (true, true) => self.base_denom == other.base_denom,
+            (false, false) => self.hashed() == other.hashed(),
+            (true, false) => self.base_denom.0 == other.hashed().to_ibc_denom(),
+            (false, true) => self.hashed().to_ibc_denom() == other.base_denom.0,
+        }
+    }
+}
+
+/// The chain of a transfer that is the source of the transferred denomination.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Source {
+    /// The sender chain, which escrows the tokens on send (and the receiver mints vouchers).
+    Sender,
+    /// The receiver chain, which unescrows the tokens on receive (and the sender burns vouchers).
+    Receiver,
 }
 
 /// Returns true if the denomination originally came from the sender chain and
@@ -212,22 +601,52 @@ pub fn is_receiver_chain_source(
     denom.trace_path.starts_with(&prefix)
 }
 
+/// Returns the denomination the receiving chain records for `denom` when it is sent from
+/// `source_port`/`source_channel` to `dest_port`/`dest_channel`, as done by `process_recv_packet`:
+/// if the receiving chain is the source of the token, the source prefix is removed, otherwise the
+/// destination prefix is added.
+pub fn receiver_denom(
+    source_port: &PortId,
+    source_channel: &ChannelId,
+    dest_port: &PortId,
+    dest_channel: &ChannelId,
+    denom: &PrefixedDenom,
+) -> PrefixedDenom {
+    let mut receiver_denom = denom.clone();
+    let source_prefix = TracePrefix::new(source_port.clone(), *source_channel);
+    match denom.source_chain(&source_prefix) {
+        Source::Receiver => receiver_denom.remove_trace_prefix(&source_prefix),
+        Source::Sender => {
+            receiver_denom.add_trace_prefix(TracePrefix::new(dest_port.clone(), *dest_channel))
+        }
+    }
+    receiver_denom
+}
+
+/// Parses a denomination of the form
+/// `{port-id-n}/{channel-id-n}/.../{port-id-1}/{channel-id-1}/{base-denom}`.
+///
+/// As in ibc-go, the trace path consists of the leading `{port-id}/{channel-id}` pairs whose second
+/// segment is a channel identifier, and the base denomination is everything after them, so base
+/// denominations containing '/' (e.g. `gamm/pool/1`) are supported. `BaseDenom::from_str` rejects
+/// base denominations that would be mistaken for a trace hop, so `Display` and `FromStr` round-trip.
 impl FromStr for PrefixedDenom {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts: Vec<&str> = s.split('/').collect();
-        let last_part = parts.pop().expect("split() returned an empty iterator");
+        let parts: Vec<&str> = s.split('/').collect();
 
-        let (base_denom, trace_path) = {
-            if last_part == s {
-                (BaseDenom::from_str(s)?, TracePath::default())
-            } else {
-                let base_denom = BaseDenom::from_str(last_part)?;
-                let trace_path = TracePath::try_from(parts)?;
-                (base_denom, trace_path)
-            }
-        };
+        let mut depth = 0;
+        while 2 * depth + 1 < parts.len() && ChannelId::from_str(parts[2 * depth + 1]).is_ok() {
+            depth += 1;
+        }
+        // e.g. "transfer/channel-0", which is a trace path rather than a denomination
+        if depth > 0 && 2 * depth == parts.len() {
+            return Err(Error::ambiguous_denom(s.to_string()));
+        }
+
+        let base_denom = BaseDenom::from_str(&parts[2 * depth..].join("/"))?;
+        let trace_path = TracePath::try_from(parts[..2 * depth].to_vec())?;
 
         Ok(Self {
             trace_path,
@@ -267,6 +686,14 @@ impl From<BaseDenom> for PrefixedDenom {
     }
 }
 
+/// A prefixed denomination equals a base denomination iff its trace path is empty (i.e. it is
+/// native) and their base denominations match.
+impl PartialEq<BaseDenom> for PrefixedDenom {
+    fn eq(&self, other: &BaseDenom) -> bool {
+        self.trace_path.is_empty() && &self.base_denom == other
+    }
+}
+
 impl fmt::Display for PrefixedDenom {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.trace_path.0.is_empty() {
@@ -277,11 +704,146 @@ impl fmt::Display for PrefixedDenom {
     }
 }
 
+/// The trace of a denomination, as stored by chains under its `HashedDenom` so that the on-chain
+/// `ibc/{HASH}` denomination of a voucher can be resolved to its full `PrefixedDenom`.
+pub type DenomTrace = PrefixedDenom;
+
+/// The SHA-256 hash of the full trace of a `PrefixedDenom`, under which chains store denomination
+/// traces and which they use as the on-chain `ibc/{HASH}` denomination of vouchers.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct HashedDenom([u8; 32]);
+
+impl HashedDenom {
+    /// Creates a hash from its raw bytes.
+    pub const fn new(hash: [u8; 32]) -> Self {
+        Self(hash)
+    }
+
+    /// Returns the `ibc/{HASH}` form of this hash, with the hash in uppercase hex.
+    pub fn to_ibc_denom(&self) -> String {
+        format!("{}/{}", IBC_DENOM_PREFIX, self)
+    }
+}
+
+impl fmt::Display for HashedDenom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02X}", byte))
+    }
+}
+
+const IBC_DENOM_PREFIX: &str = "ibc";
+
+/// Hashes `denom` the way the Cosmos SDK does, i.e. returns the SHA-256 hash of its full trace
+/// `{trace_path}/{base_denom}` (or just `{base_denom}` for native denominations), so that
+/// off-chain tooling can compute the `ibc/{HASH}` denomination a chain stores for a voucher.
+pub fn hash_denom(denom: &PrefixedDenom) -> HashedDenom {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Sha256::digest(denom.to_string().as_bytes()));
+    HashedDenom(hash)
+}
+
+/// Returns true if `hash` is the hash of `trace`, i.e. if a denomination trace stored under the
+/// key `hash` is consistent with its key.
+pub fn verify_trace_entry(hash: &HashedDenom, trace: &DenomTrace) -> bool {
+    &trace.hashed() == hash
+}
+
+/// Returns the `ibc/{HASH}` denominations of the vouchers a chain mints for `base_denom` when
+/// receiving it directly from its source over each of the given `channels` (each being the port
+/// and channel on the receiving chain's end), in the same order as the channels.
+pub fn derivable_voucher_denoms(base_denom: &BaseDenom, channels: &[TracePrefix]) -> Vec<String> {
+    channels
+        .iter()
+        .map(|channel| {
+            let mut voucher = PrefixedDenom::from(base_denom.clone());
+            voucher.add_trace_prefix(channel.clone());
+            voucher.hashed().to_ibc_denom()
+        })
+        .collect()
+}
+
+/// A hashed IBC denomination as found on the wire, i.e. `ibc/` followed by the 64 uppercase hex
+/// characters of a `HashedDenom`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct IbcDenomHash(HashedDenom);
+
+impl IbcDenomHash {
+    pub fn hashed_denom(&self) -> &HashedDenom {
+        &self.0
+    }
+}
+
+impl From<HashedDenom> for IbcDenomHash {
+    fn from(hash: HashedDenom) -> Self {
+        Self(hash)
+    }
+}
+
+impl FromStr for IbcDenomHash {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| Error::invalid_denom_hash(s.to_string(), reason.to_string());
+
+        let hex = s
+            .strip_prefix(IBC_DENOM_PREFIX)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .ok_or_else(|| invalid("missing `ibc/` prefix"))?;
+        if hex.len() != 64 {
+            return Err(invalid("hash must be 64 hex characters long"));
+        }
+        if !hex
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'A'..=b'F').contains(&b))
+        {
+            return Err(invalid("hash must only contain uppercase hex characters"));
+        }
+
+        let mut hash = [0u8; 32];
+        for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+            // the characters were checked to be ASCII hex digits above
+            let pair = core::str::from_utf8(pair).expect("ASCII is valid UTF-8");
+            *byte = u8::from_str_radix(pair, 16).expect("valid hex digits");
+        }
+
+        Ok(Self(HashedDenom(hash)))
+    }
+}
+
+impl fmt::Display for IbcDenomHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.to_ibc_denom())
+    }
+}
+
+/// A registry of human friendly display aliases (e.g. "ATOM") for hashed denominations. This is
+/// purely a display helper and has no bearing on how tokens are transferred.
+#[derive(Clone, Debug, Default)]
+pub struct AliasRegistry(BTreeMap<HashedDenom, String>);
+
+impl AliasRegistry {
+    /// Registers `alias` for the denomination with the given hash, replacing any previous alias.
+    pub fn register(&mut self, denom_hash: HashedDenom, alias: String) {
+        self.0.insert(denom_hash, alias);
+    }
+
+    /// Returns the alias registered for the hash of `denom`, if any.
+    pub fn resolve(&self, denom: &PrefixedDenom) -> Option<&str> {
+        self.0.get(&denom.hashed()).map(String::as_str)
+    }
+}
+
 /// A type for representing token transfer amounts.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Display, From, Into)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, Display, From, Into)]
 pub struct Amount(U256);
 
 impl Amount {
+    pub const ZERO: Self = Self(U256::zero());
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
     pub fn checked_add(self, rhs: Self) -> Option<Self> {
         self.0.checked_add(rhs.0).map(Self)
     }
@@ -289,6 +851,55 @@ impl Amount {
     pub fn checked_sub(self, rhs: Self) -> Option<Self> {
         self.0.checked_sub(rhs.0).map(Self)
     }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.0.checked_mul(rhs.0).map(Self)
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.0.checked_div(rhs.0).map(Self)
+    }
+
+    /// Adds `rhs`, saturating at the maximum amount instead of overflowing.
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    /// Subtracts `rhs`, saturating at zero instead of underflowing.
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Amount {
+    /// Parses a decimal amount that may use single underscores between digits as separators for
+    /// readability, e.g. "1_000_000". Leading, trailing and consecutive underscores are rejected.
+    /// Use `FromStr` to parse amounts strictly.
+    pub fn from_str_human(s: &str) -> Result<Self, Error> {
+        if s.starts_with('_') || s.ends_with('_') || s.contains("__") {
+            return Err(Error::invalid_digit_separator(s.to_string()));
+        }
+        s.replace('_', "").parse()
+    }
+
+    /// Formats the amount as a decimal number of units of `10^decimals`, without trailing zeros in
+    /// the fractional part, e.g. 1500000 with 6 decimals is formatted as "1.5" and 1 with 6
+    /// decimals as "0.000001". Use `Display` for the integer representation used on the wire.
+    pub fn to_decimal_string(&self, decimals: u8) -> String {
+        let digits = self.0.to_string();
+        let decimals = usize::from(decimals);
+        let (integer, fraction) = if digits.len() > decimals {
+            let (integer, fraction) = digits.split_at(digits.len() - decimals);
+            (integer.to_string(), fraction.to_string())
+        } else {
+            ("0".to_string(), format!("{:0>1$}", digits, decimals))
+        };
+
+        match fraction.trim_end_matches('0') {
+            "" => integer,
+            fraction => format!("{}.{}", integer, fraction),
+        }
+    }
 }
 
 impl FromStr for Amount {
@@ -306,6 +917,23 @@ impl From<u64> for Amount {
     }
 }
 
+impl From<u128> for Amount {
+    fn from(v: u128) -> Self {
+        Self(v.into())
+    }
+}
+
+impl TryFrom<Amount> for u128 {
+    type Error = Error;
+
+    fn try_from(amount: Amount) -> Result<Self, Self::Error> {
+        if amount.0.bits() > 128 {
+            return Err(Error::amount_exceeds_u128(amount));
+        }
+        Ok(amount.0.as_u128())
+    }
+}
+
 /// Coin defines a token with a denomination and an amount.
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Coin<D> {
@@ -316,15 +944,60 @@ pub struct Coin<D> {
     pub amount: Amount,
 }
 
+impl<D: PartialEq> Coin<D> {
+    /// Adds the amounts of two coins of the same denomination.
+    /// Returns `None` if the denominations differ or the sum overflows.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        if self.denom != other.denom {
+            return None;
+        }
+        let amount = self.amount.checked_add(other.amount)?;
+        Some(Self {
+            denom: self.denom,
+            amount,
+        })
+    }
+}
+
+/// Sums the amounts of the given coins per denomination. The result contains one coin per distinct
+/// denomination, sorted by denomination, and is an error if any of the sums overflows.
+pub fn sum_coins<D>(coins: &[Coin<D>]) -> Result<Vec<Coin<D>>, Error>
+where
+    D: Clone + Ord + fmt::Display,
+{
+    let mut sums: BTreeMap<&D, Amount> = BTreeMap::new();
+    for coin in coins {
+        let sum = match sums.get(&coin.denom) {
+            Some(sum) => sum
+                .checked_add(coin.amount)
+                .ok_or_else(|| Error::amount_overflow(coin.denom.to_string()))?,
+            None => coin.amount,
+        };
+        sums.insert(&coin.denom, sum);
+    }
+
+    Ok(sums
+        .into_iter()
+        .map(|(denom, amount)| Coin {
+            denom: denom.clone(),
+            amount,
+        })
+        .collect())
+}
+
+/// Fails with `Error::invalid_coin_denom` or `Error::invalid_coin_amount` if the denomination or
+/// the amount of the raw coin cannot be parsed, respectively.
 impl<D: FromStr> TryFrom<RawCoin> for Coin<D>
 where
-    Error: From<<D as FromStr>::Err>,
+    <D as FromStr>::Err: fmt::Display,
 {
     type Error = Error;
 
     fn try_from(proto: RawCoin) -> Result<Coin<D>, Self::Error> {
-        let denom = D::from_str(&proto.denom)?;
-        let amount = Amount::from_str(&proto.amount)?;
+        let denom = D::from_str(&proto.denom)
+            .map_err(|e| Error::invalid_coin_denom(proto.denom.clone(), e.to_string()))?;
+        let amount = Amount::from_str(&proto.amount)
+            .map_err(|e| Error::invalid_coin_amount(proto.amount.clone(), e.to_string()))?;
         Ok(Self { denom, amount })
     }
 }
@@ -347,6 +1020,46 @@ impl From<BaseCoin> for PrefixedCoin {
     }
 }
 
+impl PrefixedCoin {
+    /// Creates a coin of the given native denomination, i.e. with an empty trace path.
+    pub fn from_base(denom: BaseDenom, amount: Amount) -> PrefixedCoin {
+        PrefixedCoin {
+            denom: denom.into(),
+            amount,
+        }
+    }
+
+    /// Creates a coin of the native denomination parsed from `denom`, i.e. with an empty trace path.
+    /// The whole of `denom` is taken as the base denomination.
+    pub fn native(denom: &str, amount: u64) -> Result<PrefixedCoin, Error> {
+        Ok(Self::from_base(BaseDenom::from_str(denom)?, amount.into()))
+    }
+
+    /// Returns true if the amount can be represented by chains with `u128` balances.
+    pub fn fits_u128(&self) -> bool {
+        self.amount.0.bits() <= 128
+    }
+}
+
+/// A collection of `PrefixedCoin`s, e.g. the balances of an account.
+#[derive(Clone, Debug, Default, Eq, PartialEq, From, Into)]
+pub struct PrefixedCoins(Vec<PrefixedCoin>);
+
+impl PrefixedCoins {
+    /// Groups the coins by the outermost prefix of their trace path, i.e. by the channel they were
+    /// last received over. Native coins are grouped under `None`.
+    pub fn group_by_outermost(&self) -> BTreeMap<Option<TracePrefix>, Vec<PrefixedCoin>> {
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for coin in &self.0 {
+            groups
+                .entry(coin.denom.return_route())
+                .or_default()
+                .push(coin.clone());
+        }
+        groups
+    }
+}
+
 impl fmt::Display for PrefixedCoin {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}-{}", self.amount, self.denom)
@@ -356,6 +1069,8 @@ impl fmt::Display for PrefixedCoin {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::applications::transfer::error::ErrorDetail;
+    use proptest::prelude::*;
 
     #[test]
     fn test_denom_validation() -> Result<(), Error> {
@@ -373,8 +1088,8 @@ mod tests {
             "single trace"
         );
         assert!(
-            PrefixedDenom::from_str("transfer/atom").is_err(),
-            "single trace with base denom"
+            PrefixedDenom::from_str("transfer/atom")?.is_native(),
+            "base denom with a slash"
         );
         assert!(
             PrefixedDenom::from_str("transfer/channel-0/uatom").is_ok(),
@@ -415,6 +1130,10 @@ mod tests {
             "valid multiple trace info"
         );
 
+        let denom = PrefixedDenom::from_str("transfer/channel-0/uatom")?;
+        assert_eq!(denom.trace_path().to_string(), "transfer/channel-0");
+        assert_eq!(denom.base_denom().to_string(), "uatom");
+
         Ok(())
     }
 
@@ -461,6 +1180,12 @@ mod tests {
             trace_path
         );
 
+        assert_eq!(trace_path.len(), 2);
+        assert_eq!(
+            trace_path.iter().collect::<Vec<_>>(),
+            vec![&prefix_2, &prefix_1]
+        );
+
         trace_path.remove_prefix(&prefix_2);
         assert_eq!(TracePath::from_str("transfer/channel-1")?, trace_path);
         assert_eq!(TracePath(vec![prefix_1.clone()]), trace_path);
@@ -470,4 +1195,1056 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_return_route() -> Result<(), Error> {
+        let denom = PrefixedDenom::from_str("transfer/channel-0/transfer/channel-1/uatom")?;
+        assert_eq!(
+            denom.return_route(),
+            Some(TracePrefix::new(
+                "transfer".parse().unwrap(),
+                "channel-0".parse().unwrap()
+            ))
+        );
+
+        let denom = PrefixedDenom::from_str("uatom")?;
+        assert_eq!(denom.return_route(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_amount_checked_mul_div() {
+        let max = Amount::from(U256::MAX);
+        let two = Amount::from(2u64);
+
+        assert_eq!(max.checked_mul(Amount::from(1u64)), Some(max));
+        assert_eq!(max.checked_mul(two), None);
+        assert_eq!(
+            Amount::from(U256::MAX / 2).checked_mul(two),
+            Some(Amount::from(U256::MAX - 1))
+        );
+
+        assert_eq!(max.checked_div(two), Some(Amount::from(U256::MAX / 2)));
+        assert_eq!(
+            Amount::from(7u64).checked_div(two),
+            Some(Amount::from(3u64))
+        );
+        assert_eq!(max.checked_div(Amount::from(0u64)), None);
+    }
+
+    #[test]
+    fn test_extra_hops_over() -> Result<(), Error> {
+        let denom = PrefixedDenom::from_str("transfer/channel-0/transfer/channel-1/uatom")?;
+
+        assert_eq!(
+            denom.extra_hops_over(&"transfer/channel-1".parse()?),
+            Some(vec![TracePrefix::new(
+                "transfer".parse().unwrap(),
+                "channel-0".parse().unwrap()
+            )])
+        );
+        assert_eq!(
+            denom.extra_hops_over(&"transfer/channel-0/transfer/channel-1".parse()?),
+            Some(vec![])
+        );
+        assert_eq!(denom.extra_hops_over(&"transfer/channel-0".parse()?), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_path_max_depth() {
+        let path_of_depth = |depth: usize| {
+            (0..depth)
+                .map(|i| format!("transfer/channel-{}", i))
+                .collect::<Vec<_>>()
+                .join("/")
+        };
+
+        let path = TracePath::from_str(&path_of_depth(MAX_TRACE_DEPTH)).unwrap();
+        assert_eq!(path.len(), MAX_TRACE_DEPTH);
+
+        let err = TracePath::from_str(&path_of_depth(MAX_TRACE_DEPTH + 1)).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::TracePathTooLong(e) if e.depth == MAX_TRACE_DEPTH + 1 && e.max == MAX_TRACE_DEPTH
+        ));
+    }
+
+    #[test]
+    fn test_minimal_path() -> Result<(), Error> {
+        let pass_throughs = [TracePrefix::new(PortId::transfer(), ChannelId::new(5))]
+            .into_iter()
+            .collect();
+
+        let denom = PrefixedDenom::from_str(
+            "transfer/channel-0/transfer/channel-5/transfer/channel-1/uatom",
+        )?;
+        assert_eq!(
+            denom.minimal_path_with(&pass_throughs).to_string(),
+            "transfer/channel-0/transfer/channel-1"
+        );
+        assert_eq!(denom.minimal_path(), *denom.trace_path());
+
+        // repeated channel IDs are hops through different chains
+        let denom = PrefixedDenom::from_str(
+            "transfer/channel-0/transfer/channel-0/transfer/channel-1/uatom",
+        )?;
+        assert_eq!(denom.minimal_path_with(&pass_throughs), *denom.trace_path());
+
+        // the hop over which the token left its source chain is kept
+        let denom = PrefixedDenom::from_str("transfer/channel-5/transfer/channel-5/uatom")?;
+        assert_eq!(
+            denom.minimal_path_with(&pass_throughs).to_string(),
+            "transfer/channel-5"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_coin_checked_add() -> Result<(), Error> {
+        let atom = |amount: u64| PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: amount.into(),
+        };
+        let osmo = PrefixedCoin {
+            denom: "transfer/channel-0/uosmo".parse()?,
+            amount: 1u64.into(),
+        };
+
+        assert_eq!(atom(1).checked_add(atom(2)), Some(atom(3)));
+        assert_eq!(atom(1).checked_add(osmo), None);
+
+        let max = PrefixedCoin {
+            amount: U256::MAX.into(),
+            ..atom(0)
+        };
+        assert_eq!(max.checked_add(atom(1)), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_coins() -> Result<(), Error> {
+        let coin = |denom: &str, amount: u64| -> Result<PrefixedCoin, Error> {
+            Ok(PrefixedCoin {
+                denom: denom.parse()?,
+                amount: amount.into(),
+            })
+        };
+
+        // single denom
+        assert_eq!(
+            sum_coins(&[coin("uatom", 1)?, coin("uatom", 2)?])?,
+            vec![coin("uatom", 3)?]
+        );
+
+        // mixed denoms, output is sorted by denom regardless of the input order
+        let expected = vec![coin("uatom", 3)?, coin("transfer/channel-0/uosmo", 5)?];
+        expected
+            .windows(2)
+            .for_each(|w| assert!(w[0].denom < w[1].denom));
+        assert_eq!(
+            sum_coins(&[
+                coin("uatom", 1)?,
+                coin("transfer/channel-0/uosmo", 5)?,
+                coin("uatom", 2)?,
+            ])?,
+            expected
+        );
+        assert_eq!(
+            sum_coins(&[
+                coin("transfer/channel-0/uosmo", 5)?,
+                coin("uatom", 2)?,
+                coin("uatom", 1)?,
+            ])?,
+            expected
+        );
+
+        // overflow
+        let max = PrefixedCoin {
+            amount: U256::MAX.into(),
+            ..coin("uatom", 0)?
+        };
+        let err = sum_coins(&[max, coin("uatom", 1)?]).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::AmountOverflow(_)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_hashed_base() -> Result<(), Error> {
+        let hashed: PrefixedDenom = BaseDenom::from_str(
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+        )?
+        .into();
+        assert!(hashed.has_hashed_base());
+
+        assert!(!PrefixedDenom::from_str("uatom")?.has_hashed_base());
+        assert!(!PrefixedDenom::from_str("transfer/channel-0/uatom")?.has_hashed_base());
+        let not_a_hash: PrefixedDenom = BaseDenom::from_str("ibc/uatom")?.into();
+        assert!(!not_a_hash.has_hashed_base());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_amount_zero() {
+        assert!(Amount::ZERO.is_zero());
+        assert_eq!(Amount::ZERO, Amount::from(0u64));
+        assert!(!Amount::from(1u64).is_zero());
+    }
+
+    /// Number of chains in the network simulated by `test_send_recv_inverse`. Every pair of chains
+    /// is connected by a channel, whose end on chain `i` is `transfer/channel-{j}` if it leads to
+    /// chain `j`, so that the same channel identifiers occur on every chain.
+    const CHAINS: usize = 3;
+
+    /// Returns the denomination under which `denom` is credited on chain `to` when sent from chain
+    /// `from` in the simulated network.
+    fn transfer_between(denom: &PrefixedDenom, from: usize, to: usize) -> PrefixedDenom {
+        receiver_denom(
+            &PortId::transfer(),
+            &ChannelId::new(to as u64),
+            &PortId::transfer(),
+            &ChannelId::new(from as u64),
+            denom,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn test_send_recv_inverse(
+            hops in prop::collection::vec(1..CHAINS, 0..6),
+            offset in 1..CHAINS,
+        ) {
+            // Reach the denomination under test by transferring a native one, so that only states
+            // that real transfers can produce are generated. Hops are offsets to the next chain.
+            let mut chain = 0;
+            let mut denom = PrefixedDenom::from(BaseDenom::from_str("uatom").unwrap());
+            for hop in hops {
+                let next = (chain + hop) % CHAINS;
+                denom = transfer_between(&denom, chain, next);
+                chain = next;
+            }
+
+            let counterparty = (chain + offset) % CHAINS;
+            let on_counterparty = transfer_between(&denom, chain, counterparty);
+            prop_assert_eq!(
+                transfer_between(&on_counterparty, counterparty, chain),
+                denom,
+                "round trip through {}",
+                on_counterparty
+            );
+        }
+    }
+
+    #[test]
+    fn test_ibc_denom_hash() {
+        let denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        let hash = IbcDenomHash::from_str(denom).unwrap();
+        assert_eq!(hash.to_string(), denom);
+        assert_eq!(hash.hashed_denom().to_ibc_denom(), denom);
+        assert_eq!(
+            hash.hashed_denom().to_string(),
+            "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+
+        for invalid in [
+            "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+            "IBC/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+            "ibc27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+            "ibc/27394fb092d2eccd56123c74f36e4c1f926001ceada9ca97ea622b25f41e5eb2",
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB",
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2A",
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EBG",
+            "ibc/",
+            "uatom",
+        ] {
+            let err = IbcDenomHash::from_str(invalid).unwrap_err();
+            assert!(
+                matches!(err.detail(), ErrorDetail::InvalidDenomHash(_)),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    /// Generates base denominations, including ones with '/' such as `gamm/pool/1` and ones with
+    /// channel identifiers in later segments.
+    fn base_denom_strategy() -> impl Strategy<Value = BaseDenom> {
+        let segment = prop_oneof![
+            "[a-zA-Z0-9:._-]{1,10}",
+            (0..1000u64).prop_map(|n| ChannelId::new(n).to_string()),
+        ];
+        let generic = (
+            "[a-zA-Z][a-zA-Z0-9:._-]{2,30}",
+            prop::collection::vec(segment, 0..4),
+        )
+            .prop_map(|(first, rest)| [vec![first], rest].concat().join("/"));
+        let pool = (0..1000u64).prop_map(|n| format!("gamm/pool/{}", n));
+        prop_oneof![generic, pool].prop_filter_map("invalid base denomination", |denom| {
+            BaseDenom::from_str(&denom).ok()
+        })
+    }
+
+    fn trace_path_strategy() -> impl Strategy<Value = TracePath> {
+        let prefix = ("[a-z0-9._+#\\[\\]<>-]{2,20}", 0..1000u64).prop_map(|(port_id, channel)| {
+            TracePrefix::new(port_id.parse().unwrap(), ChannelId::new(channel))
+        });
+        prop::collection::vec(prefix, 0..5).prop_map(TracePath::from_prefixes)
+    }
+
+    proptest! {
+        #[test]
+        fn test_denom_display_from_str_round_trip(
+            trace_path in trace_path_strategy(),
+            base_denom in base_denom_strategy(),
+        ) {
+            let denom = PrefixedDenom { trace_path, base_denom };
+            let formatted = denom.to_string();
+            prop_assert_eq!(PrefixedDenom::from_str(&formatted).ok(), Some(denom), "{}", formatted);
+        }
+    }
+
+    #[test]
+    fn test_denom_with_slashed_base_round_trip() -> Result<(), Error> {
+        let denom = PrefixedDenom::try_from(RawDenomTrace {
+            path: "transfer/channel-0".to_string(),
+            base_denom: "gamm/pool/1".to_string(),
+        })?;
+        assert_eq!(denom.to_string(), "transfer/channel-0/gamm/pool/1");
+        assert_eq!(PrefixedDenom::from_str(&denom.to_string())?, denom);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_base_denom_validation() {
+        let max_len = "a".repeat(128);
+        let over_len = "a".repeat(129);
+
+        for valid in [
+            "uatom",
+            "atom",
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+            "gamm/pool/1",
+            "cw20:juno1abc",
+            "a.b_c-d",
+            max_len.as_str(),
+        ] {
+            assert!(BaseDenom::from_str(valid).is_ok(), "{}", valid);
+        }
+
+        for invalid in [
+            "has space",
+            "ab",
+            over_len.as_str(),
+            "uat\0om",
+            "1atom",
+            "uatom!",
+            "gamm//pool",
+            "gamm/pool/",
+        ] {
+            let err = BaseDenom::from_str(invalid).unwrap_err();
+            assert!(
+                matches!(err.detail(), ErrorDetail::InvalidBaseDenom(_)),
+                "{}",
+                invalid
+            );
+        }
+
+        for ambiguous in [
+            "transfer/channel-0",
+            "transfer/channel-0/uatom",
+            "ab/channel-1",
+        ] {
+            let err = BaseDenom::from_str(ambiguous).unwrap_err();
+            assert!(
+                matches!(err.detail(), ErrorDetail::AmbiguousBaseDenom(_)),
+                "{}",
+                ambiguous
+            );
+        }
+        assert!(BaseDenom::from_str("gamm/pool/channel-1").is_ok());
+    }
+
+    #[test]
+    fn test_group_by_outermost() -> Result<(), Error> {
+        let coin = |denom: &str| -> Result<PrefixedCoin, Error> {
+            Ok(PrefixedCoin {
+                denom: denom.parse()?,
+                amount: 1u64.into(),
+            })
+        };
+        let via_hub = coin("transfer/channel-0/uatom")?;
+        let via_hub_multi_hop = coin("transfer/channel-0/transfer/channel-5/ujuno")?;
+        let via_osmosis = coin("transfer/channel-1/uosmo")?;
+        let native = coin("stake")?;
+
+        let coins = PrefixedCoins::from(vec![
+            via_hub.clone(),
+            native.clone(),
+            via_osmosis.clone(),
+            via_hub_multi_hop.clone(),
+        ]);
+        let groups = coins.group_by_outermost();
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&None], vec![native]);
+        assert_eq!(
+            groups[&Some(TracePrefix::new(PortId::transfer(), ChannelId::new(0)))],
+            vec![via_hub, via_hub_multi_hop]
+        );
+        assert_eq!(
+            groups[&Some(TracePrefix::new(PortId::transfer(), ChannelId::new(1)))],
+            vec![via_osmosis]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_chain() -> Result<(), Error> {
+        let prefix = TracePrefix::new(PortId::transfer(), ChannelId::new(0));
+
+        for (denom, source) in [
+            ("uatom", Source::Sender),
+            ("transfer/channel-1/uatom", Source::Sender),
+            (
+                "transfer/channel-1/transfer/channel-0/uatom",
+                Source::Sender,
+            ),
+            ("transfer/channel-0/uatom", Source::Receiver),
+            (
+                "transfer/channel-0/transfer/channel-1/uatom",
+                Source::Receiver,
+            ),
+        ] {
+            let denom = PrefixedDenom::from_str(denom)?;
+            assert_eq!(denom.source_chain(&prefix), source, "{}", denom);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_amount_from_str_human() {
+        assert_eq!(
+            Amount::from_str_human("1_000_000").unwrap(),
+            Amount::from(1_000_000u64)
+        );
+        assert_eq!(Amount::from_str_human("100").unwrap(), Amount::from(100u64));
+        assert!(Amount::from_str("1_000_000").is_err());
+
+        for invalid in ["1__000", "_100", "100_", "_", "1_a00"] {
+            assert!(Amount::from_str_human(invalid).is_err(), "{}", invalid);
+        }
+        for invalid in ["1__000", "_100", "100_"] {
+            assert!(matches!(
+                Amount::from_str_human(invalid).unwrap_err().detail(),
+                ErrorDetail::InvalidDigitSeparator(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_derivable_voucher_denoms() -> Result<(), Error> {
+        let channels = [
+            TracePrefix::new(PortId::transfer(), ChannelId::new(0)),
+            TracePrefix::new(PortId::transfer(), ChannelId::new(1)),
+        ];
+
+        assert_eq!(
+            derivable_voucher_denoms(&"uatom".parse()?, &channels),
+            vec![
+                "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+                "ibc/C4CFF46FD6DE35CA4CF4CE031E643C8FDC9BA4B99AE598E9B0ED98FE3A2319F9",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_onchain_denom() -> Result<(), Error> {
+        let voucher = PrefixedDenom::from_str("transfer/channel-0/uatom")?;
+        let mut constructed = PrefixedDenom::from(BaseDenom::from_str("uatom")?);
+        constructed.add_trace_prefix(TracePrefix::new(PortId::transfer(), ChannelId::new(0)));
+        assert!(voucher.same_onchain_denom(&constructed));
+        assert!(constructed.same_onchain_denom(&voucher));
+
+        let other_channel = PrefixedDenom::from_str("transfer/channel-1/uatom")?;
+        assert!(!voucher.same_onchain_denom(&other_channel));
+
+        let native = PrefixedDenom::from_str("uatom")?;
+        assert!(native.same_onchain_denom(&PrefixedDenom::from_str("uatom")?));
+        assert!(!native.same_onchain_denom(&voucher));
+        assert!(!native.same_onchain_denom(&PrefixedDenom::from_str("uosmo")?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_alias_registry() -> Result<(), Error> {
+        let atom_hash = IbcDenomHash::from_str(
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+        )?;
+        let mut registry = AliasRegistry::default();
+        registry.register(*atom_hash.hashed_denom(), "ATOM".to_string());
+
+        let atom = PrefixedDenom::from_str("transfer/channel-0/uatom")?;
+        assert_eq!(registry.resolve(&atom), Some("ATOM"));
+
+        let other = PrefixedDenom::from_str("transfer/channel-1/uatom")?;
+        assert_eq!(registry.resolve(&other), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_path_all_known() -> Result<(), Error> {
+        let known: BTreeSet<_> = [0, 1]
+            .into_iter()
+            .map(|n| TracePrefix::new(PortId::transfer(), ChannelId::new(n)))
+            .collect();
+
+        assert!(TracePath::from_str("")?.all_known(&known));
+        assert!(TracePath::from_str("transfer/channel-1/transfer/channel-0")?.all_known(&known));
+        assert!(!TracePath::from_str("transfer/channel-1/transfer/channel-2")?.all_known(&known));
+        assert!(
+            !TracePath::from_str("transfer/channel-0/transfer/channel-1")?
+                .all_known(&BTreeSet::new())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefixed_denom_eq_base_denom() -> Result<(), Error> {
+        let uatom = BaseDenom::from_str("uatom")?;
+
+        assert_eq!(PrefixedDenom::from_str("uatom")?, uatom);
+        assert_ne!(PrefixedDenom::from_str("transfer/channel-0/uatom")?, uatom);
+        assert_ne!(PrefixedDenom::from_str("uosmo")?, uatom);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_path_to_segments() -> Result<(), Error> {
+        let path = TracePath::from_str("transfer/channel-0/transfer/channel-1")?;
+        assert_eq!(
+            path.to_segments(),
+            ["transfer", "channel-0", "transfer", "channel-1"]
+        );
+        assert!(TracePath::from_str("")?.to_segments().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_base_denom_eq_ignore_ascii_case() -> Result<(), Error> {
+        let uatom = BaseDenom::from_str("uatom")?;
+        assert!(uatom.eq_ignore_ascii_case("UATOM"));
+        assert!(uatom.eq_ignore_ascii_case("uAtOm"));
+        assert!(!uatom.eq_ignore_ascii_case("uosmo"));
+        assert_ne!(uatom, BaseDenom::from_str("UATOM")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_path_first_last_prefix() -> Result<(), Error> {
+        let path = TracePath::from_str("transfer/channel-0/transfer/channel-1")?;
+        let prefix = |n| TracePrefix::new(PortId::transfer(), ChannelId::new(n));
+
+        assert_eq!(path.first_prefix(), Some(&prefix(1)));
+        assert_eq!(path.last_prefix(), Some(&prefix(0)));
+
+        let mut extended = path.clone();
+        extended.add_prefix(prefix(2));
+        assert_eq!(extended.first_prefix(), Some(&prefix(1)));
+        assert_eq!(extended.last_prefix(), Some(&prefix(2)));
+
+        let empty = TracePath::from_str("")?;
+        assert_eq!(empty.first_prefix(), None);
+        assert_eq!(empty.last_prefix(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_trace_entry() -> Result<(), Error> {
+        let trace = DenomTrace::from_str("transfer/channel-0/uatom")?;
+        let hash = *IbcDenomHash::from_str(
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+        )?
+        .hashed_denom();
+        assert!(verify_trace_entry(&hash, &trace));
+
+        let other = DenomTrace::from_str("transfer/channel-1/uatom")?;
+        assert!(!verify_trace_entry(&hash, &other));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_path_extend() -> Result<(), Error> {
+        let a = TracePath::from_str("transfer/channel-0")?;
+        let b = TracePath::from_str("transfer/channel-1")?;
+
+        let combined = a.clone() + b.clone();
+        assert_eq!(
+            combined.to_string(),
+            "transfer/channel-1/transfer/channel-0"
+        );
+
+        let mut extended = a.clone();
+        extended.extend(b);
+        assert_eq!(extended, combined);
+
+        let mut via_prefix = a;
+        via_prefix.add_prefix(TracePrefix::new(PortId::transfer(), ChannelId::new(1)));
+        assert_eq!(via_prefix, combined);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_native() -> Result<(), Error> {
+        assert!(PrefixedDenom::from_str("uatom")?.is_native());
+        assert!(!PrefixedDenom::from_str("transfer/channel-0/uatom")?.is_native());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_coin_try_from_raw_coin() {
+        let raw = |denom: &str, amount: &str| RawCoin {
+            denom: denom.to_string(),
+            amount: amount.to_string(),
+        };
+
+        let coin = PrefixedCoin::try_from(raw("transfer/channel-0/uatom", "10")).unwrap();
+        assert_eq!(coin.denom.to_string(), "transfer/channel-0/uatom");
+        assert_eq!(coin.amount, Amount::from(10u64));
+
+        let err = PrefixedCoin::try_from(raw("", "10")).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::InvalidCoinDenom(e) if e.denom.is_empty()));
+
+        let err = BaseCoin::try_from(raw("uatom", "ten")).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::InvalidCoinAmount(e) if e.amount == "ten"));
+    }
+
+    #[test]
+    fn test_canonicalize_via() -> Result<(), Error> {
+        let prefix = |n| TracePrefix::new(PortId::transfer(), ChannelId::new(n));
+        let equivalences = [(prefix(5), prefix(0))].into_iter().collect();
+
+        let denom = PrefixedDenom::from_str("transfer/channel-5/transfer/channel-7/uatom")?;
+        assert_eq!(
+            denom.canonicalize_via(&equivalences),
+            PrefixedDenom::from_str("transfer/channel-0/transfer/channel-7/uatom")?
+        );
+
+        let canonical = PrefixedDenom::from_str("transfer/channel-0/uatom")?;
+        assert_eq!(canonical.canonicalize_via(&equivalences), canonical);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_path_strip_prefix() -> Result<(), Error> {
+        let path = TracePath::from_str("transfer/channel-0/transfer/channel-1")?;
+
+        let stripped = path.strip_prefix(&TracePrefix::new(PortId::transfer(), ChannelId::new(0)));
+        assert_eq!(stripped, Some(TracePath::from_str("transfer/channel-1")?));
+        assert_eq!(
+            path.strip_prefix(&TracePrefix::new(PortId::transfer(), ChannelId::new(1))),
+            None
+        );
+        assert_eq!(
+            path,
+            TracePath::from_str("transfer/channel-0/transfer/channel-1")?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_denom_hash_map_keys() -> Result<(), Error> {
+        use std::collections::HashMap;
+
+        let native = PrefixedDenom::from_str("uatom")?;
+        let voucher = PrefixedDenom::from_str("transfer/channel-0/uatom")?;
+        let other_voucher = PrefixedDenom::from_str("transfer/channel-1/uatom")?;
+
+        let mut balances = HashMap::new();
+        balances.insert(native.clone(), Amount::from(1u64));
+        balances.insert(voucher.clone(), Amount::from(2u64));
+        balances.insert(other_voucher.clone(), Amount::from(3u64));
+        assert_eq!(balances.len(), 3);
+
+        assert_eq!(balances.get(&native), Some(&Amount::from(1u64)));
+        assert_eq!(
+            balances.get(&PrefixedDenom::from_str("transfer/channel-0/uatom")?),
+            Some(&Amount::from(2u64))
+        );
+        assert_eq!(balances.get(&other_voucher), Some(&Amount::from(3u64)));
+
+        let mut paths = HashMap::new();
+        paths.insert(voucher.trace_path.clone(), voucher.base_denom.clone());
+        assert_eq!(
+            paths.get(&TracePath::from_str("transfer/channel-0")?),
+            Some(&BaseDenom::from_str("uatom")?)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefixed_denom_from_str_lenient() -> Result<(), Error> {
+        let sloppy = "transfer//channel-0//uatom";
+        assert!(PrefixedDenom::from_str(sloppy).is_err());
+        assert_eq!(
+            PrefixedDenom::from_str_lenient(sloppy)?,
+            PrefixedDenom::from_str("transfer/channel-0/uatom")?
+        );
+        assert_eq!(
+            PrefixedDenom::from_str_lenient("transfer///channel-0/transfer/channel-1//uatom")?,
+            PrefixedDenom::from_str("transfer/channel-0/transfer/channel-1/uatom")?
+        );
+        assert_eq!(
+            PrefixedDenom::from_str_lenient("uatom")?,
+            PrefixedDenom::from_str("uatom")?
+        );
+        assert!(PrefixedDenom::from_str_lenient("transfer//channel-0//").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transfer_preview() -> Result<(), Error> {
+        let native = PrefixedDenom::from_str("uatom")?;
+
+        let preview = native.transfer_preview(&PortId::transfer(), ChannelId::new(0));
+        assert_eq!(preview.source_denom, native);
+        assert_eq!(
+            preview.destination_denom,
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_denom() -> Result<(), Error> {
+        let denom = PrefixedDenom::from_str("transfer/channel-0/uatom")?;
+        let expected = HashedDenom::new([
+            0x27, 0x39, 0x4F, 0xB0, 0x92, 0xD2, 0xEC, 0xCD, 0x56, 0x12, 0x3C, 0x74, 0xF3, 0x6E,
+            0x4C, 0x1F, 0x92, 0x60, 0x01, 0xCE, 0xAD, 0xA9, 0xCA, 0x97, 0xEA, 0x62, 0x2B, 0x25,
+            0xF4, 0x1E, 0x5E, 0xB2,
+        ]);
+
+        assert_eq!(hash_denom(&denom), expected);
+        assert_eq!(denom.hashed(), expected);
+        assert_eq!(
+            expected.to_ibc_denom(),
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_amount_to_decimal_string() {
+        let amount = |amount: u64| Amount::from(amount);
+
+        // whole numbers
+        assert_eq!(amount(0).to_decimal_string(6), "0");
+        assert_eq!(amount(2_000_000).to_decimal_string(6), "2");
+        assert_eq!(amount(1234).to_decimal_string(0), "1234");
+        assert_eq!(amount(1230).to_decimal_string(0), "1230");
+
+        // fractional values
+        assert_eq!(amount(1_500_000).to_decimal_string(6), "1.5");
+        assert_eq!(amount(12_345_678).to_decimal_string(6), "12.345678");
+
+        // sub-unit amounts
+        assert_eq!(amount(1).to_decimal_string(6), "0.000001");
+        assert_eq!(amount(999_990).to_decimal_string(6), "0.99999");
+        assert_eq!(amount(1).to_decimal_string(18), "0.000000000000000001");
+
+        // the wire representation is unchanged
+        assert_eq!(amount(1_500_000).to_string(), "1500000");
+    }
+
+    #[test]
+    fn test_trace_path_contains() -> Result<(), Error> {
+        let path = TracePath::from_str("transfer/channel-0/transfer/channel-1/transfer/channel-2")?;
+        let hop =
+            |channel_id: u64| TracePrefix::new(PortId::transfer(), ChannelId::new(channel_id));
+
+        // first (i.e. outermost) hop
+        assert!(path.contains(&hop(0)));
+        assert!(path.starts_with(&hop(0)));
+        // middle hop
+        assert!(path.contains(&hop(1)));
+        assert!(!path.starts_with(&hop(1)));
+        // last hop
+        assert!(path.contains(&hop(2)));
+        assert!(!path.starts_with(&hop(2)));
+        // absent
+        assert!(!path.contains(&hop(3)));
+        assert!(!TracePath::default().contains(&hop(0)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_trace_segment_in_error() {
+        let err = TracePath::from_str("transfer/channel-0/transfer/chan!nel-1").unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::InvalidTraceChannelId(_)
+        ));
+        assert!(err.to_string().contains("\"chan!nel-1\""), "{}", err);
+
+        let err = PrefixedDenom::from_str("tr@nsfer/channel-0/uatom").unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::InvalidTracePortId(_)));
+        assert!(err.to_string().contains("\"tr@nsfer\""), "{}", err);
+    }
+
+    #[test]
+    fn test_trace_path_from_prefixes() -> Result<(), Error> {
+        let prefixes = vec![
+            TracePrefix::new(PortId::transfer(), ChannelId::new(0)),
+            TracePrefix::new(PortId::transfer(), ChannelId::new(1)),
+        ];
+        let expected = TracePath::from_str("transfer/channel-0/transfer/channel-1")?;
+
+        let path = TracePath::from_prefixes(prefixes.clone());
+        assert_eq!(path.to_string(), "transfer/channel-0/transfer/channel-1");
+        assert_eq!(path, expected);
+        assert!(path.starts_with(&prefixes[0]));
+
+        let path: TracePath = prefixes.into_iter().collect();
+        assert_eq!(path, expected);
+
+        assert!(TracePath::from_prefixes(vec![]).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_native_prefixed_coin() -> Result<(), Error> {
+        let coin = PrefixedCoin::from_base(BaseDenom::from_str("uatom")?, Amount::from(10u64));
+        assert!(coin.denom.trace_path().is_empty());
+        assert_eq!(coin.denom, PrefixedDenom::from_str("uatom")?);
+        assert_eq!(coin.amount, Amount::from(10u64));
+
+        let coin = PrefixedCoin::native("uatom", 10)?;
+        assert!(coin.denom.trace_path().is_empty());
+        assert_eq!(coin.denom.base_denom(), &BaseDenom::from_str("uatom")?);
+        assert_eq!(coin.amount, Amount::from(10u64));
+
+        assert!(PrefixedCoin::native("", 10).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_denom_trace_from_full_denom() -> Result<(), Error> {
+        let trace = DenomTrace::from_full_denom("transfer/channel-0/uatom")?;
+        assert_eq!(trace.trace_path().to_string(), "transfer/channel-0");
+        assert_eq!(trace.base_denom(), &BaseDenom::from_str("uatom")?);
+
+        let matching = IbcDenomHash::from_str(
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+        )?;
+        assert!(trace.matches_ibc_denom(&matching));
+
+        let mismatching = IbcDenomHash::from_str(
+            "ibc/C4CFF46FD6DE35CA4CF4CE031E643C8FDC9BA4B99AE598E9B0ED98FE3A2319F9",
+        )?;
+        assert!(!trace.matches_ibc_denom(&mismatching));
+
+        assert!(DenomTrace::from_full_denom("transfer/channel-0").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_amount_saturating_ops() {
+        let max = Amount::from(U256::MAX);
+        let one = Amount::from(1u64);
+        let two = Amount::from(2u64);
+
+        assert_eq!(max.saturating_add(one), max);
+        assert_eq!(Amount::from(U256::MAX - 1).saturating_add(one), max);
+        assert_eq!(one.saturating_add(one), two);
+
+        assert_eq!(Amount::ZERO.saturating_sub(one), Amount::ZERO);
+        assert_eq!(one.saturating_sub(two), Amount::ZERO);
+        assert_eq!(two.saturating_sub(one), one);
+        assert_eq!(max.saturating_sub(max), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_ambiguous_denom() -> Result<(), Error> {
+        for ambiguous in [
+            "transfer/channel-0",
+            "transfer/channel-0/transfer/channel-1",
+        ] {
+            let err = PrefixedDenom::from_str(ambiguous).unwrap_err();
+            assert!(
+                matches!(err.detail(), ErrorDetail::AmbiguousDenom(_)),
+                "{}",
+                ambiguous
+            );
+        }
+
+        // a channel-shaped base denomination is legitimate if the trace path is well formed
+        let denom = PrefixedDenom::from_str("transfer/channel-0/channel-1")?;
+        assert_eq!(denom.base_denom(), &BaseDenom::from_str("channel-1")?);
+        assert!(PrefixedDenom::from_str("channel-0")?.is_native());
+        assert!(PrefixedDenom::from_str("transfer/channel-0/uatom").is_ok());
+
+        // without a channel, the whole string is the base denomination
+        let denom = PrefixedDenom::from_str("transfer/uatom")?;
+        assert!(denom.is_native());
+        assert_eq!(denom.base_denom(), &BaseDenom::from_str("transfer/uatom")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_path_parse_streaming() {
+        let too_long = "transfer/channel-0/".repeat(MAX_TRACE_DEPTH + 1);
+        let too_long = too_long.trim_end_matches('/');
+        let paths = [
+            "",
+            " ",
+            "transfer/channel-0",
+            "transfer/channel-0/transfer/channel-1",
+            "transfer/channel-0/",
+            "transfer/",
+            "transfer",
+            "/",
+            "//",
+            "(transfer)/channel-0",
+            "transfer/(channel-0)",
+            "(transfer)/channel-0/transfer/(channel-1)",
+            "(transfer)/(channel-0)/transfer/channel-1",
+            "transfer/channel-0/transfer",
+            too_long,
+        ];
+
+        for path in paths {
+            match (TracePath::from_str(path), TracePath::parse_streaming(path)) {
+                (Ok(expected), Ok(streamed)) => assert_eq!(expected, streamed, "{:?}", path),
+                (Err(expected), Err(streamed)) => assert_eq!(
+                    format!("{:?}", expected.detail()),
+                    format!("{:?}", streamed.detail()),
+                    "{:?}",
+                    path
+                ),
+                (expected, streamed) => {
+                    panic!("{:?}: expected {:?}, got {:?}", path, expected, streamed)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_receiver_denom() -> Result<(), Error> {
+        let port_id = PortId::transfer();
+        let (source_channel, dest_channel) = (ChannelId::new(0), ChannelId::new(1));
+        let receiver_denom_of = |denom: &str| -> Result<String, Error> {
+            let denom = PrefixedDenom::from_str(denom)?;
+            Ok(
+                receiver_denom(&port_id, &source_channel, &port_id, &dest_channel, &denom)
+                    .to_string(),
+            )
+        };
+
+        // the sender chain is the source, the destination prefix is added
+        assert_eq!(receiver_denom_of("uatom")?, "transfer/channel-1/uatom");
+        assert_eq!(
+            receiver_denom_of("transfer/channel-5/uatom")?,
+            "transfer/channel-1/transfer/channel-5/uatom"
+        );
+        assert_eq!(
+            PrefixedDenom::from_str(&receiver_denom_of("uatom")?)?
+                .hashed()
+                .to_ibc_denom(),
+            "ibc/C4CFF46FD6DE35CA4CF4CE031E643C8FDC9BA4B99AE598E9B0ED98FE3A2319F9"
+        );
+
+        // the receiver chain is the source, the source prefix is removed
+        assert_eq!(receiver_denom_of("transfer/channel-0/uatom")?, "uatom");
+        assert_eq!(
+            receiver_denom_of("transfer/channel-0/transfer/channel-5/uatom")?,
+            "transfer/channel-5/uatom"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_amount_u128_conversion() {
+        for value in [0u128, 1, u64::MAX as u128 + 1, u128::MAX] {
+            let amount = Amount::from(value);
+            assert_eq!(amount.to_string(), value.to_string());
+            assert_eq!(u128::try_from(amount).unwrap(), value);
+        }
+
+        let too_large = Amount::from(U256::from(u128::MAX) + U256::one());
+        let err = u128::try_from(too_large).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::AmountExceedsU128(_)));
+    }
+
+    #[test]
+    fn test_native_denom_equality() -> Result<(), Error> {
+        use core::hash::{Hash, Hasher};
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |denom: &PrefixedDenom| {
+            let mut hasher = DefaultHasher::new();
+            denom.hash(&mut hasher);
+            hasher.finish()
+        };
+        let base_denom = BaseDenom::from_str("uatom")?;
+        let with_path = |trace_path: TracePath| PrefixedDenom {
+            trace_path,
+            base_denom: base_denom.clone(),
+        };
+
+        let mut emptied = TracePath::from_str("transfer/channel-0")?;
+        emptied.remove_prefix(&TracePrefix::new(PortId::transfer(), ChannelId::new(0)));
+
+        let denoms = [
+            PrefixedDenom::from_str("uatom")?,
+            PrefixedDenom::from(base_denom.clone()),
+            PrefixedDenom::try_from(RawDenomTrace {
+                path: "".to_string(),
+                base_denom: "uatom".to_string(),
+            })?,
+            with_path(TracePath::default()),
+            with_path(TracePath(vec![])),
+            with_path(TracePath(Vec::with_capacity(4))),
+            with_path(TracePath::from_prefixes(vec![])),
+            with_path(TracePath::from_str("")?),
+            with_path(emptied),
+        ];
+        for denom in &denoms {
+            assert_eq!(denom, &denoms[0]);
+            assert_eq!(hash(denom), hash(&denoms[0]));
+        }
+
+        Ok(())
+    }
 }
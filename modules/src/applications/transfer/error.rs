@@ -1,17 +1,19 @@
 use alloc::string::FromUtf8Error;
+use core::convert::Infallible;
 
 use flex_error::{define_error, DisplayOnly, TraceError};
 use subtle_encoding::Error as EncodingError;
 use tendermint_proto::Error as TendermintProtoError;
 use uint::FromStrRadixErr;
 
+use crate::applications::transfer::{Amount, HashedDenom, PrefixedCoin, PrefixedDenom};
 use crate::core::ics04_channel::channel::Order;
 use crate::core::ics04_channel::error as channel_error;
 use crate::core::ics04_channel::Version;
 use crate::core::ics24_host::error::ValidationError;
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::prelude::*;
-use crate::signer::SignerError;
+use crate::signer::{Signer, SignerError};
 
 define_error! {
     #[derive(Debug, PartialEq, Eq)]
@@ -46,6 +48,10 @@ define_error! {
             { timestamp: u64 }
             | _ | { "invalid packet timeout timestamp value" },
 
+        InvalidTimeout
+            { reason: String }
+            | e | { format_args!("invalid packet timeout: {0}", e.reason) },
+
         Utf8
             [ DisplayOnly<FromUtf8Error> ]
             | _ | { "utf8 decoding error" },
@@ -53,24 +59,44 @@ define_error! {
         EmptyBaseDenom
             |_| { "base denomination is empty" },
 
+        InvalidBaseDenom
+            { denom: String, reason: String }
+            | e | { format_args!("invalid base denomination {0:?}: {1}", e.denom, e.reason) },
+
         InvalidTracePortId
-            { pos: usize }
+            { pos: usize, segment: String }
             [ ValidationError ]
-            | e | { format_args!("invalid port id in trace at position: {0}", e.pos) },
+            | e | { format_args!("invalid port id {1:?} in trace at position: {0}", e.pos, e.segment) },
 
         InvalidTraceChannelId
-            { pos: usize }
+            { pos: usize, segment: String }
             [ ValidationError ]
-            | e | { format_args!("invalid channel id in trace at position: {0}", e.pos) },
+            | e | { format_args!("invalid channel id {1:?} in trace at position: {0}", e.pos, e.segment) },
 
         InvalidTraceLength
             { len: usize }
             | e | { format_args!("trace length must be even but got: {0}", e.len) },
 
+        AmbiguousDenom
+            { denom: String }
+            | e | { format_args!("denomination {0:?} ends with a channel identifier instead of a base denomination, is the base denomination missing?", e.denom) },
+
+        AmbiguousBaseDenom
+            { denom: String }
+            | e | { format_args!("base denomination {0:?} starts with a port and channel identifier and cannot be told apart from a trace path", e.denom) },
+
+        TracePathTooLong
+            { depth: usize, max: usize }
+            | e | { format_args!("trace path has {0} hops, exceeding the maximum of {1}", e.depth, e.max) },
+
         InvalidAmount
             [ TraceError<FromStrRadixErr> ]
             | _ | { "invalid amount" },
 
+        InvalidDigitSeparator
+            { amount: String }
+            | e | { format_args!("invalid use of '_' digit separators in amount {0}", e.amount) },
+
         InvalidToken
             | _ | { "invalid token" },
 
@@ -100,10 +126,18 @@ define_error! {
             { version: Version }
             | e | { format_args!("expected version '{0}', got '{1}'", Version::ics20(), e.version) },
 
+        UnrecognizedVersion
+            { version: String }
+            | e | { format_args!("unrecognized ICS20 version '{0}'", e.version) },
+
         InvalidCounterpartyVersion
             { version: Version }
             | e | { format_args!("expected counterparty version '{0}', got '{1}'", Version::ics20(), e.version) },
 
+        ChannelClosed
+            { channel_id: ChannelId }
+            | e | { format_args!("channel {0} is not open", e.channel_id) },
+
         CantCloseChannel
             | _ | { "channel cannot be closed" },
 
@@ -113,12 +147,89 @@ define_error! {
         AckDeserialization
             | _ | { "failed to deserialize acknowledgement" },
 
+        EmptyAcknowledgement
+            | _ | { "acknowledgement cannot be empty" },
+
         ReceiveDisabled
             | _ | { "receive is not enabled" },
 
         SendDisabled
             | _ | { "send is not enabled" },
 
+        AmountExceedsU128
+            { amount: Amount }
+            | e | { format_args!("amount {0} exceeds the maximum amount {1} supported by the counterparty chain", e.amount, u128::MAX) },
+
+        InvalidDenomHash
+            { denom: String, reason: String }
+            | e | { format_args!("invalid hashed denomination {0}: {1}", e.denom, e.reason) },
+
+        HashedBaseDenom
+            { denom: PrefixedDenom }
+            | e | { format_args!("base denomination of {0} is an unresolved IBC hash", e.denom) },
+
+        MismatchedSourceChannel
+            { denom: PrefixedDenom, port_id: PortId, channel_id: ChannelId }
+            | e | { format_args!("voucher {0} was not received over port {1} and channel {2} it is sent over", e.denom, e.port_id, e.channel_id) },
+
+        UnknownTracePrefix
+            { denom: PrefixedDenom }
+            | e | { format_args!("voucher {0} was received over an unknown channel", e.denom) },
+
+        MissingPacketDataField
+            { field: String }
+            | e | { format_args!("missing required packet data field: {0}", e.field) },
+
+        PacketDataTooLarge
+            { field: String, len: usize, max_len: usize }
+            | e | { format_args!("packet data field {0} is {1} bytes long, exceeding the maximum of {2} bytes", e.field, e.len, e.max_len) },
+
+        AmountPrecisionLoss
+            { amount: Amount, excess_decimals: u8 }
+            | e | { format_args!("amount {0} cannot be represented locally without losing its {1} least significant decimals", e.amount, e.excess_decimals) },
+
+        ForbiddenRecipient
+            { receiver: Signer }
+            | e | { format_args!("transfers to {0} are forbidden", e.receiver) },
+
+        InvalidCoinDenom
+            { denom: String, reason: String }
+            | e | { format_args!("invalid coin denomination {0}: {1}", e.denom, e.reason) },
+
+        InvalidCoinAmount
+            { amount: String, reason: String }
+            | e | { format_args!("invalid coin amount {0}: {1}", e.amount, e.reason) },
+
+        InsufficientEscrow
+            { coin: PrefixedCoin, escrowed: Amount }
+            | e | { format_args!("insufficient escrow: cannot unescrow {0}, only {1} escrowed", e.coin, e.escrowed) },
+
+        SupplyOverflow
+            { coin: PrefixedCoin, supply: Amount }
+            | e | { format_args!("cannot mint {0}: the supply of {1} would overflow or exceed its cap", e.coin, e.supply) },
+
+        DenomNotAllowed
+            { denom: PrefixedDenom }
+            | e | { format_args!("denomination {0} is not allowed", e.denom) },
+
+        InvalidFee
+            { fee: Amount, amount: Amount }
+            | e | { format_args!("transfer fee {0} must be less than the transferred amount {1}", e.fee, e.amount) },
+
+        ZeroAmount
+            | _ | { "cannot transfer a zero amount" },
+
+        EmptyTransfer
+            | _ | { "no coins specified for transfer" },
+
+        AmountOverflow
+            { denom: String }
+            | e | { format_args!("overflow while adding amounts of denomination {0}", e.denom) },
+
+        InsufficientFunds
+            { coin: PrefixedCoin, available: Amount }
+            | e | { format_args!("insufficient funds: required {0}, available {1}", e.coin, e.available) },
+
         ParseAccountFailure
             | _ | { "failed to parse as AccountId" },
 
@@ -129,6 +240,10 @@ define_error! {
         TraceNotFound
             | _ | { "no trace associated with specified hash" },
 
+        MismatchedDenomTraceHash
+            { hash: HashedDenom, denom: PrefixedDenom }
+            | e | { format_args!("denomination trace {0} is not stored under its own hash but under {1}", e.denom, e.hash) },
+
         DecodeRawMsg
             [ TraceError<TendermintProtoError> ]
             | _ | { "error decoding raw msg" },
@@ -136,5 +251,23 @@ define_error! {
         UnknownMsgType
             { msg_type: String }
             | e | { format_args!("unknown msg type: {0}", e.msg_type) },
+
+        IncorrectEventKind
+            { kind: String, expected: String }
+            | e | { format_args!("incorrect event kind: expected '{1}', got '{0}'", e.kind, e.expected) },
+
+        MissingEventAttribute
+            { key: String }
+            | e | { format_args!("missing event attribute: {0}", e.key) },
+
+        InvalidEventAttribute
+            { key: String, value: String }
+            | e | { format_args!("invalid value '{1}' of event attribute {0}", e.key, e.value) },
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(e: Infallible) -> Self {
+        match e {}
     }
 }
@@ -0,0 +1,143 @@
+//! Bookkeeping of the tokens escrowed by the ICS20 application on each of its channels.
+
+use alloc::collections::btree_map::BTreeMap;
+
+use crate::applications::transfer::error::Error;
+use crate::applications::transfer::{Amount, PrefixedCoin, PrefixedDenom};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
+
+/// Tracks the amount of each denomination held in escrow for every `(port, channel)` pair, i.e.
+/// the amount of native tokens that can be unescrowed when vouchers return over that channel.
+/// Hosts may use it to back `Ics20Reader::channel_escrow_balances`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChannelEscrowLedger(BTreeMap<(PortId, ChannelId, PrefixedDenom), Amount>);
+
+impl ChannelEscrowLedger {
+    /// Returns the amount of `denom` escrowed on the given channel.
+    pub fn escrowed(
+        &self,
+        port_id: &PortId,
+        channel_id: ChannelId,
+        denom: &PrefixedDenom,
+    ) -> Amount {
+        self.0
+            .get(&(port_id.clone(), channel_id, denom.clone()))
+            .copied()
+            .unwrap_or(Amount::ZERO)
+    }
+
+    /// Records `coin` as escrowed on the given channel.
+    /// Fails with `Error::amount_overflow` if the escrowed amount would overflow.
+    pub fn escrow(
+        &mut self,
+        port_id: &PortId,
+        channel_id: ChannelId,
+        coin: &PrefixedCoin,
+    ) -> Result<(), Error> {
+        let escrowed = self
+            .escrowed(port_id, channel_id, &coin.denom)
+            .checked_add(coin.amount)
+            .ok_or_else(|| Error::amount_overflow(coin.denom.to_string()))?;
+        self.0
+            .insert((port_id.clone(), channel_id, coin.denom.clone()), escrowed);
+        Ok(())
+    }
+
+    /// Releases `coin` from the escrow of the given channel.
+    /// Fails with `Error::insufficient_escrow` if less than `coin` is escrowed on that channel, in
+    /// which case the ledger is left untouched.
+    pub fn unescrow(
+        &mut self,
+        port_id: &PortId,
+        channel_id: ChannelId,
+        coin: &PrefixedCoin,
+    ) -> Result<(), Error> {
+        let key = (port_id.clone(), channel_id, coin.denom.clone());
+        let escrowed = self.escrowed(port_id, channel_id, &coin.denom);
+        let remaining = escrowed
+            .checked_sub(coin.amount)
+            .ok_or_else(|| Error::insufficient_escrow(coin.clone(), escrowed))?;
+
+        if remaining.is_zero() {
+            self.0.remove(&key);
+        } else {
+            self.0.insert(key, remaining);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::transfer::error::ErrorDetail;
+
+    fn uatom(amount: u64) -> PrefixedCoin {
+        PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: amount.into(),
+        }
+    }
+
+    #[test]
+    fn test_escrow_accumulates() {
+        let mut ledger = ChannelEscrowLedger::default();
+        let port_id = PortId::transfer();
+
+        ledger
+            .escrow(&port_id, ChannelId::new(0), &uatom(10))
+            .unwrap();
+        ledger
+            .escrow(&port_id, ChannelId::new(0), &uatom(5))
+            .unwrap();
+        ledger
+            .escrow(&port_id, ChannelId::new(1), &uatom(7))
+            .unwrap();
+
+        let denom = uatom(0).denom;
+        assert_eq!(
+            ledger.escrowed(&port_id, ChannelId::new(0), &denom),
+            Amount::from(15u64)
+        );
+        assert_eq!(
+            ledger.escrowed(&port_id, ChannelId::new(1), &denom),
+            Amount::from(7u64)
+        );
+        assert_eq!(
+            ledger.escrowed(&port_id, ChannelId::new(2), &denom),
+            Amount::ZERO
+        );
+
+        ledger
+            .unescrow(&port_id, ChannelId::new(0), &uatom(15))
+            .unwrap();
+        assert_eq!(
+            ledger.escrowed(&port_id, ChannelId::new(0), &denom),
+            Amount::ZERO
+        );
+    }
+
+    #[test]
+    fn test_unescrow_underflow() {
+        let mut ledger = ChannelEscrowLedger::default();
+        let port_id = PortId::transfer();
+        ledger
+            .escrow(&port_id, ChannelId::new(0), &uatom(10))
+            .unwrap();
+
+        let err = ledger
+            .unescrow(&port_id, ChannelId::new(0), &uatom(11))
+            .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::InsufficientEscrow(_)));
+        assert_eq!(
+            ledger.escrowed(&port_id, ChannelId::new(0), &uatom(0).denom),
+            Amount::from(10u64)
+        );
+
+        // tokens escrowed on another channel cannot be unescrowed
+        assert!(ledger
+            .unescrow(&port_id, ChannelId::new(1), &uatom(1))
+            .is_err());
+    }
+}
@@ -1,5 +1,10 @@
+use core::str::FromStr;
+
 use crate::applications::transfer::acknowledgement::Acknowledgement;
-use crate::applications::transfer::{Amount, PrefixedDenom, MODULE_ID_STR};
+use crate::applications::transfer::error::Error;
+use crate::applications::transfer::packet::Memo;
+use crate::applications::transfer::{Amount, PrefixedCoin, PrefixedDenom, MODULE_ID_STR};
+use crate::core::ics04_channel::packet::Sequence;
 use crate::events::ModuleEvent;
 use crate::prelude::*;
 use crate::signer::Signer;
@@ -8,6 +13,7 @@ const EVENT_TYPE_PACKET: &str = "fungible_token_packet";
 const EVENT_TYPE_TIMEOUT: &str = "timeout";
 const EVENT_TYPE_DENOM_TRACE: &str = "denomination_trace";
 const EVENT_TYPE_TRANSFER: &str = "ibc_transfer";
+const EVENT_TYPE_RECV_TRANSFER: &str = "ibc_recv_transfer";
 
 pub enum Event {
     Recv(RecvEvent),
@@ -16,12 +22,14 @@ pub enum Event {
     Timeout(TimeoutEvent),
     DenomTrace(DenomTraceEvent),
     Transfer(TransferEvent),
+    RecvTransfer(RecvTransferEvent),
 }
 
 pub struct RecvEvent {
     pub receiver: Signer,
     pub denom: PrefixedDenom,
     pub amount: Amount,
+    pub memo: Memo,
     pub success: bool,
 }
 
@@ -31,6 +39,7 @@ impl From<RecvEvent> for ModuleEvent {
             receiver,
             denom,
             amount,
+            memo,
             success,
         } = ev;
         Self {
@@ -40,6 +49,7 @@ impl From<RecvEvent> for ModuleEvent {
                 ("receiver", receiver).into(),
                 ("denom", denom).into(),
                 ("amount", amount).into(),
+                ("memo", memo).into(),
                 ("success", success).into(),
             ],
         }
@@ -145,11 +155,30 @@ impl From<DenomTraceEvent> for ModuleEvent {
 pub struct TransferEvent {
     pub sender: Signer,
     pub receiver: Signer,
+    pub coin: PrefixedCoin,
+    pub sequence: Sequence,
+}
+
+impl TransferEvent {
+    /// Returns the event as key/value pairs, e.g. to be recorded as the attributes of a tracing
+    /// span. Unlike the emitted `ModuleEvent`, this includes the transferred coin and the sequence
+    /// of the sent packet.
+    pub fn to_attributes(&self) -> Vec<(String, String)> {
+        vec![
+            ("sender".to_string(), self.sender.to_string()),
+            ("receiver".to_string(), self.receiver.to_string()),
+            ("amount".to_string(), self.coin.amount.to_string()),
+            ("denom".to_string(), self.coin.denom.to_string()),
+            ("sequence".to_string(), self.sequence.to_string()),
+        ]
+    }
 }
 
 impl From<TransferEvent> for ModuleEvent {
     fn from(ev: TransferEvent) -> Self {
-        let TransferEvent { sender, receiver } = ev;
+        let TransferEvent {
+            sender, receiver, ..
+        } = ev;
         Self {
             kind: EVENT_TYPE_TRANSFER.to_string(),
             module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
@@ -158,6 +187,110 @@ impl From<TransferEvent> for ModuleEvent {
     }
 }
 
+/// How the tokens of a received transfer were credited to the receiver.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RecvAction {
+    /// The receiving chain is the source of the tokens, which were released from escrow.
+    Unescrow,
+    /// The sending chain is the source of the tokens, for which vouchers were minted.
+    Mint,
+}
+
+impl RecvAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecvAction::Unescrow => "unescrow",
+            RecvAction::Mint => "mint",
+        }
+    }
+}
+
+impl core::fmt::Display for RecvAction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for RecvAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unescrow" => Ok(RecvAction::Unescrow),
+            "mint" => Ok(RecvAction::Mint),
+            _ => Err(Error::invalid_event_attribute(
+                "action".to_string(),
+                s.to_string(),
+            )),
+        }
+    }
+}
+
+/// Emitted when the tokens of a received transfer are credited to the receiver. Its kind differs
+/// from the one of the `TransferEvent` emitted on send, so that sends and receives can be told
+/// apart.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecvTransferEvent {
+    /// The sender on the counterparty chain
+    pub sender: Signer,
+    pub receiver: Signer,
+    /// The coin credited to the receiver, with its denomination as known on this chain
+    pub coin: PrefixedCoin,
+    pub action: RecvAction,
+}
+
+impl From<RecvTransferEvent> for ModuleEvent {
+    fn from(ev: RecvTransferEvent) -> Self {
+        let RecvTransferEvent {
+            sender,
+            receiver,
+            coin,
+            action,
+        } = ev;
+        Self {
+            kind: EVENT_TYPE_RECV_TRANSFER.to_string(),
+            module_name: MODULE_ID_STR.parse().expect("invalid ModuleId"),
+            attributes: vec![
+                ("sender", sender).into(),
+                ("receiver", receiver).into(),
+                ("denom", coin.denom).into(),
+                ("amount", coin.amount).into(),
+                ("action", action).into(),
+            ],
+        }
+    }
+}
+
+impl TryFrom<ModuleEvent> for RecvTransferEvent {
+    type Error = Error;
+
+    fn try_from(ev: ModuleEvent) -> Result<Self, Self::Error> {
+        if ev.kind != EVENT_TYPE_RECV_TRANSFER {
+            return Err(Error::incorrect_event_kind(
+                ev.kind,
+                EVENT_TYPE_RECV_TRANSFER.to_string(),
+            ));
+        }
+
+        let attribute = |key: &str| {
+            ev.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .map(|attr| attr.value.as_str())
+                .ok_or_else(|| Error::missing_event_attribute(key.to_string()))
+        };
+        Ok(Self {
+            sender: attribute("sender")?.parse().map_err(Error::signer)?,
+            receiver: attribute("receiver")?.parse().map_err(Error::signer)?,
+            coin: PrefixedCoin {
+                denom: attribute("denom")?.parse()?,
+                amount: attribute("amount")?.parse()?,
+            },
+            action: attribute("action")?.parse()?,
+        })
+    }
+}
+
 impl From<Event> for ModuleEvent {
     fn from(ev: Event) -> Self {
         match ev {
@@ -167,6 +300,90 @@ impl From<Event> for ModuleEvent {
             Event::Timeout(ev) => ev.into(),
             Event::DenomTrace(ev) => ev.into(),
             Event::Transfer(ev) => ev.into(),
+            Event::RecvTransfer(ev) => ev.into(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_dummy_account_id;
+
+    #[test]
+    fn test_transfer_event_to_attributes() {
+        let event = TransferEvent {
+            sender: get_dummy_account_id(),
+            receiver: "cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng"
+                .parse()
+                .unwrap(),
+            coin: PrefixedCoin {
+                denom: "transfer/channel-0/uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            sequence: 7.into(),
+        };
+
+        let attributes = event.to_attributes();
+        let expected = [
+            ("sender", get_dummy_account_id().to_string()),
+            (
+                "receiver",
+                "cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng".to_string(),
+            ),
+            ("amount", "10".to_string()),
+            ("denom", "transfer/channel-0/uatom".to_string()),
+            ("sequence", "7".to_string()),
+        ];
+        assert_eq!(attributes.len(), expected.len());
+        for ((key, value), (expected_key, expected_value)) in attributes.iter().zip(expected) {
+            assert_eq!(key, expected_key);
+            assert_eq!(value, &expected_value);
+        }
+    }
+
+    #[test]
+    fn test_recv_transfer_event_round_trip() {
+        for action in [RecvAction::Unescrow, RecvAction::Mint] {
+            let event = RecvTransferEvent {
+                sender: get_dummy_account_id(),
+                receiver: "cosmos1wxeyh7zgn4tctjzs0vtqpc6p5cxq5t2muzl7ng"
+                    .parse()
+                    .unwrap(),
+                coin: PrefixedCoin {
+                    denom: "transfer/channel-0/uatom".parse().unwrap(),
+                    amount: 10u64.into(),
+                },
+                action,
+            };
+
+            let module_event = ModuleEvent::from(event.clone());
+            assert_eq!(module_event.kind, "ibc_recv_transfer");
+            assert_eq!(RecvTransferEvent::try_from(module_event).unwrap(), event);
+        }
+
+        // a send-side transfer event is not a receive
+        let send_event = ModuleEvent::from(TransferEvent {
+            sender: get_dummy_account_id(),
+            receiver: get_dummy_account_id(),
+            coin: PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            sequence: 1.into(),
+        });
+        assert!(RecvTransferEvent::try_from(send_event).is_err());
+
+        let mut module_event = ModuleEvent::from(RecvTransferEvent {
+            sender: get_dummy_account_id(),
+            receiver: get_dummy_account_id(),
+            coin: PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            action: RecvAction::Mint,
+        });
+        module_event.attributes.retain(|attr| attr.key != "action");
+        assert!(RecvTransferEvent::try_from(module_event).is_err());
+    }
+}
@@ -5,13 +5,19 @@ pub mod acknowledgement;
 pub mod context;
 pub mod denom;
 pub mod error;
+pub mod escrow;
 pub mod events;
 pub mod msgs;
 pub mod packet;
 pub mod relay;
 
+use core::fmt;
+use core::str::FromStr;
+
 pub use denom::*;
 
+use crate::prelude::*;
+
 /// Module identifier for the ICS20 application.
 pub const MODULE_ID_STR: &str = "transfer";
 
@@ -21,3 +27,62 @@ pub const PORT_ID_STR: &str = "transfer";
 
 /// ICS20 application current version.
 pub const VERSION: &str = "ics20-1";
+
+/// The versions of the ICS20 application that are recognized by this implementation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Ics20Version {
+    /// `ics20-1`, the single-denomination fungible token packet format.
+    V1,
+    /// `ics20-2`, the multi-denomination fungible token packet format.
+    V2,
+}
+
+impl Ics20Version {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => VERSION,
+            Self::V2 => "ics20-2",
+        }
+    }
+}
+
+impl FromStr for Ics20Version {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            VERSION => Ok(Self::V1),
+            "ics20-2" => Ok(Self::V2),
+            _ => Err(error::Error::unrecognized_version(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Ics20Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ics04_channel::Version;
+
+    #[test]
+    fn test_ics20_version() {
+        assert_eq!("ics20-1".parse::<Ics20Version>().unwrap(), Ics20Version::V1);
+        assert_eq!("ics20-2".parse::<Ics20Version>().unwrap(), Ics20Version::V2);
+        assert_eq!(Ics20Version::V1.to_string(), VERSION);
+
+        assert!("ics20".parse::<Ics20Version>().is_err());
+        assert!("ics20-3".parse::<Ics20Version>().is_err());
+        assert!("ics27-1".parse::<Ics20Version>().is_err());
+        assert!("".parse::<Ics20Version>().is_err());
+
+        assert!(Version::ics20().is_ics20());
+        assert!(Version::new("ics20-2".to_string()).is_ics20());
+        assert!(!Version::new("ics20-3".to_string()).is_ics20());
+        assert!(!Version::empty().is_ics20());
+    }
+}
@@ -2,12 +2,15 @@
 
 use crate::prelude::*;
 
+use core::time::Duration;
+
 use ibc_proto::cosmos::base::v1beta1::Coin;
 use ibc_proto::google::protobuf::Any;
 use ibc_proto::ibc::applications::transfer::v1::MsgTransfer as RawMsgTransfer;
 use tendermint_proto::Protobuf;
 
 use crate::applications::transfer::error::Error;
+use crate::applications::transfer::packet::Memo;
 use crate::core::ics02_client::height::Height;
 use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::signer::Signer;
@@ -16,6 +19,10 @@ use crate::tx_msg::Msg;
 
 pub const TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
 
+/// The timeout relative to the host time used by `MsgTransfer::new_with_default_timeout`, matching
+/// the default relative packet timeout of the Cosmos SDK transfer CLI.
+pub const DEFAULT_TIMEOUT_DURATION: Duration = Duration::from_secs(10 * 60);
+
 /// Message used to build an ICS20 token transfer packet.
 ///
 /// Note that this message is not a packet yet, as it lacks the proper sequence
@@ -41,6 +48,65 @@ pub struct MsgTransfer<C = Coin> {
     /// Timeout timestamp relative to the current block timestamp.
     /// The timeout is disabled when set to 0.
     pub timeout_timestamp: Timestamp,
+    /// An optional memo to be carried along with the packet data
+    pub memo: Memo,
+}
+
+impl<C> MsgTransfer<C> {
+    /// Creates a transfer message with an empty memo.
+    /// Fails if both the timeout height and the timeout timestamp are disabled, as such a packet
+    /// could never time out.
+    pub fn new_with_timeout(
+        token: C,
+        sender: Signer,
+        receiver: Signer,
+        source_port: PortId,
+        source_channel: ChannelId,
+        timeout_height: Height,
+        timeout_timestamp: Timestamp,
+    ) -> Result<Self, Error> {
+        if timeout_height.is_zero() && timeout_timestamp == Timestamp::none() {
+            return Err(Error::invalid_timeout(
+                "timeout height and timeout timestamp cannot both be disabled".to_string(),
+            ));
+        }
+
+        Ok(MsgTransfer {
+            source_port,
+            source_channel,
+            token,
+            sender,
+            receiver,
+            timeout_height,
+            timeout_timestamp,
+            memo: Memo::default(),
+        })
+    }
+
+    /// Same as `new_with_timeout`, but without a timeout height and with a timeout timestamp of
+    /// `DEFAULT_TIMEOUT_DURATION` after the given `host_timestamp`, i.e. the current time of the
+    /// sending chain.
+    pub fn new_with_default_timeout(
+        token: C,
+        sender: Signer,
+        receiver: Signer,
+        source_port: PortId,
+        source_channel: ChannelId,
+        host_timestamp: Timestamp,
+    ) -> Result<Self, Error> {
+        let timeout_timestamp = (host_timestamp + DEFAULT_TIMEOUT_DURATION)
+            .map_err(|_| Error::invalid_packet_timeout_timestamp(host_timestamp.nanoseconds()))?;
+
+        Self::new_with_timeout(
+            token,
+            sender,
+            receiver,
+            source_port,
+            source_channel,
+            Height::zero(),
+            timeout_timestamp,
+        )
+    }
 }
 
 impl Msg for MsgTransfer {
@@ -84,6 +150,7 @@ impl TryFrom<RawMsgTransfer> for MsgTransfer {
             receiver: raw_msg.receiver.parse().map_err(Error::signer)?,
             timeout_height,
             timeout_timestamp,
+            memo: raw_msg.memo.into(),
         })
     }
 }
@@ -98,6 +165,7 @@ impl From<MsgTransfer> for RawMsgTransfer {
             receiver: domain_msg.receiver.to_string(),
             timeout_height: Some(domain_msg.timeout_height.into()),
             timeout_timestamp: domain_msg.timeout_timestamp.nanoseconds(),
+            memo: domain_msg.memo.to_string(),
         }
     }
 }
@@ -160,6 +228,75 @@ pub mod test_util {
                 revision_number: 0,
                 revision_height: height,
             },
+            memo: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::transfer::error::ErrorDetail;
+    use crate::applications::transfer::PrefixedCoin;
+    use crate::test_utils::get_dummy_account_id;
+
+    #[test]
+    fn test_new_with_timeout() {
+        let token = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let sender = get_dummy_account_id();
+        let receiver: Signer = "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a"
+            .parse()
+            .unwrap();
+        let timeout_height = Height::new(0, 100);
+
+        let msg = MsgTransfer::new_with_timeout(
+            token.clone(),
+            sender.clone(),
+            receiver.clone(),
+            PortId::transfer(),
+            ChannelId::new(0),
+            timeout_height,
+            Timestamp::none(),
+        )
+        .unwrap();
+        assert_eq!(msg.token, token);
+        assert_eq!(msg.sender, sender);
+        assert_eq!(msg.receiver, receiver);
+        assert_eq!(msg.source_port, PortId::transfer());
+        assert_eq!(msg.source_channel, ChannelId::new(0));
+        assert_eq!(msg.timeout_height, timeout_height);
+        assert_eq!(msg.timeout_timestamp, Timestamp::none());
+        assert!(msg.memo.is_empty());
+
+        let err = MsgTransfer::new_with_timeout(
+            token.clone(),
+            sender.clone(),
+            receiver.clone(),
+            PortId::transfer(),
+            ChannelId::new(0),
+            Height::zero(),
+            Timestamp::none(),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::InvalidTimeout(_)));
+
+        let host_timestamp = Timestamp::from_nanoseconds(1_000_000_000).unwrap();
+        let msg = MsgTransfer::new_with_default_timeout(
+            token,
+            sender,
+            receiver,
+            PortId::transfer(),
+            ChannelId::new(0),
+            host_timestamp,
+        )
+        .unwrap();
+        assert!(msg.timeout_height.is_zero());
+        assert_eq!(
+            msg.timeout_timestamp,
+            (host_timestamp + DEFAULT_TIMEOUT_DURATION).unwrap()
+        );
+    }
+}
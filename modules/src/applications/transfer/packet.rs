@@ -1,12 +1,15 @@
-use alloc::string::ToString;
+use alloc::string::{String, ToString};
 use core::convert::TryFrom;
+use core::fmt;
 use core::str::FromStr;
 
 use ibc_proto::ibc::applications::transfer::v2::FungibleTokenPacketData as RawPacketData;
 use serde::{Deserialize, Serialize};
 
 use super::error::Error;
-use super::{Amount, PrefixedCoin, PrefixedDenom};
+use super::{is_receiver_chain_source, Amount, PrefixedCoin, PrefixedDenom};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
+use crate::prelude::*;
 use crate::signer::Signer;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -14,6 +17,208 @@ pub struct PacketData {
     pub token: PrefixedCoin,
     pub sender: Signer,
     pub receiver: Signer,
+    #[serde(default, skip_serializing_if = "Memo::is_empty")]
+    pub memo: Memo,
+}
+
+impl PacketData {
+    /// Returns all the tokens transferred by the packet. This is always exactly one token for v1
+    /// packet data, but callers written against this method keep working with multi-token (v2)
+    /// packet data.
+    pub fn tokens(&self) -> Vec<&PrefixedCoin> {
+        vec![&self.token]
+    }
+
+    /// Returns true if this packet, sent over `source_port` and `source_channel`, returns a voucher
+    /// to the chain it originally came from, i.e. if the outermost prefix of the denomination is
+    /// exactly that port and channel. The receiving chain then unescrows its native token instead
+    /// of minting a voucher.
+    pub fn is_return_to_source(&self, source_port: &PortId, source_channel: ChannelId) -> bool {
+        is_receiver_chain_source(source_port.clone(), source_channel, &self.token.denom)
+    }
+
+    /// Checks that the sender, receiver and memo do not exceed the given maximum lengths, failing
+    /// with `Error::packet_data_too_large` otherwise.
+    pub fn validate(&self, limits: &PacketDataLimits) -> Result<(), Error> {
+        let fields = [
+            ("sender", self.sender.as_ref(), limits.max_sender_len),
+            ("receiver", self.receiver.as_ref(), limits.max_receiver_len),
+            ("memo", self.memo.as_str(), limits.max_memo_len),
+        ];
+        for (field, value, max_len) in fields {
+            if value.len() > max_len {
+                return Err(Error::packet_data_too_large(
+                    field.to_string(),
+                    value.len(),
+                    max_len,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The maximum lengths, in bytes, of the variable-size fields of `PacketData`, which keep senders
+/// from bloating packets and their commitments. The defaults match the limits of the Cosmos SDK
+/// transfer module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PacketDataLimits {
+    pub max_sender_len: usize,
+    pub max_receiver_len: usize,
+    pub max_memo_len: usize,
+}
+
+impl Default for PacketDataLimits {
+    fn default() -> Self {
+        Self {
+            max_sender_len: 2048,
+            max_receiver_len: 2048,
+            max_memo_len: 32768,
+        }
+    }
+}
+
+/// The optional memo carried by an ICS20 packet, e.g. instructions for packet-forward-middleware
+/// or IBC hooks. An empty memo is omitted from the serialized packet data.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Memo(String);
+
+impl Memo {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the forwarding instruction carried by the memo, if it parses as one.
+    pub fn forward(&self) -> Option<ForwardMemo> {
+        #[derive(Deserialize)]
+        struct ForwardEnvelope {
+            forward: ForwardMemo,
+        }
+
+        serde_json::from_str::<ForwardEnvelope>(&self.0)
+            .ok()
+            .map(|envelope| envelope.forward)
+    }
+}
+
+/// A packet-forward-middleware instruction to transfer the received tokens onwards, carried in the
+/// memo as `{"forward":{"receiver":"...","port":"...","channel":"..."}}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardMemo {
+    /// The receiver on the next chain.
+    pub receiver: Signer,
+    /// The port to forward the tokens over.
+    pub port: PortId,
+    /// The channel to forward the tokens over.
+    pub channel: ChannelId,
+}
+
+impl AsRef<str> for Memo {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Memo {
+    fn from(memo: String) -> Self {
+        Self(memo)
+    }
+}
+
+impl FromStr for Memo {
+    type Err = core::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl fmt::Display for Memo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Cheaply checks whether `data` looks like JSON encoded ICS20 packet data, i.e. is a JSON object
+/// containing the `denom`, `amount`, `sender` and `receiver` keys. This does not deserialize the
+/// data, so a `true` result does not guarantee that parsing it as `PacketData` succeeds.
+pub fn looks_like_ics20(data: &[u8]) -> bool {
+    let contains = |key: &[u8]| data.windows(key.len()).any(|window| window == key);
+
+    data.iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .map_or(false, |&b| b == b'{')
+        && [
+            &br#""denom""#[..],
+            br#""amount""#,
+            br#""sender""#,
+            br#""receiver""#,
+        ]
+        .into_iter()
+        .all(contains)
+}
+
+/// A builder for `PacketData` that validates the packet data on `build()`.
+#[derive(Clone, Debug, Default)]
+pub struct PacketDataBuilder {
+    token: Option<PrefixedCoin>,
+    sender: Option<Signer>,
+    receiver: Option<Signer>,
+    memo: Memo,
+}
+
+impl PacketDataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_token(self, token: PrefixedCoin) -> Self {
+        Self {
+            token: Some(token),
+            ..self
+        }
+    }
+
+    pub fn with_sender(self, sender: Signer) -> Self {
+        Self {
+            sender: Some(sender),
+            ..self
+        }
+    }
+
+    pub fn with_receiver(self, receiver: Signer) -> Self {
+        Self {
+            receiver: Some(receiver),
+            ..self
+        }
+    }
+
+    pub fn with_memo(self, memo: Memo) -> Self {
+        Self { memo, ..self }
+    }
+
+    /// Builds the `PacketData`, failing if the token, sender or receiver is missing or if the
+    /// token amount is zero.
+    pub fn build(self) -> Result<PacketData, Error> {
+        let missing = |field: &str| Error::missing_packet_data_field(field.to_string());
+
+        let token = self.token.ok_or_else(|| missing("token"))?;
+        if token.amount.is_zero() {
+            return Err(Error::zero_amount());
+        }
+
+        Ok(PacketData {
+            token,
+            sender: self.sender.ok_or_else(|| missing("sender"))?,
+            receiver: self.receiver.ok_or_else(|| missing("receiver"))?,
+            memo: self.memo,
+        })
+    }
 }
 
 impl TryFrom<RawPacketData> for PacketData {
@@ -27,6 +232,7 @@ impl TryFrom<RawPacketData> for PacketData {
             token: PrefixedCoin { denom, amount },
             sender: raw_pkt_data.sender.parse().map_err(Error::signer)?,
             receiver: raw_pkt_data.receiver.parse().map_err(Error::signer)?,
+            memo: raw_pkt_data.memo.into(),
         })
     }
 }
@@ -38,6 +244,151 @@ impl From<PacketData> for RawPacketData {
             amount: pkt_data.token.amount.to_string(),
             sender: pkt_data.sender.to_string(),
             receiver: pkt_data.receiver.to_string(),
+            memo: pkt_data.memo.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::transfer::error::ErrorDetail;
+    use crate::test_utils::get_dummy_account_id;
+
+    fn get_dummy_packet_data(memo: Memo) -> PacketData {
+        PacketData {
+            token: PrefixedCoin {
+                denom: "transfer/channel-0/uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            sender: get_dummy_account_id(),
+            receiver: get_dummy_account_id(),
+            memo,
+        }
+    }
+
+    #[test]
+    fn test_packet_data_memo_round_trip() {
+        for memo in [
+            "",
+            r#"{"forward":{"port":"transfer","channel":"channel-1"}}"#,
+        ] {
+            let data = get_dummy_packet_data(memo.parse().unwrap());
+
+            let raw = RawPacketData::from(data.clone());
+            assert_eq!(raw.memo, memo);
+            assert_eq!(PacketData::try_from(raw).unwrap(), data);
+
+            let json = serde_json::to_vec(&data).unwrap();
+            assert_eq!(serde_json::from_slice::<PacketData>(&json).unwrap(), data);
         }
     }
+
+    #[test]
+    fn test_packet_data_without_memo_serializes_as_before() {
+        let json = serde_json::to_value(get_dummy_packet_data(Memo::default())).unwrap();
+        assert!(json.get("memo").is_none());
+
+        let json = serde_json::to_value(get_dummy_packet_data("hello".parse().unwrap())).unwrap();
+        assert_eq!(json["memo"], "hello");
+    }
+
+    #[test]
+    fn test_packet_data_builder() {
+        let data = get_dummy_packet_data("hello".parse().unwrap());
+        let builder = PacketDataBuilder::new()
+            .with_token(data.token.clone())
+            .with_receiver(data.receiver.clone())
+            .with_memo(data.memo.clone());
+
+        let err = builder.clone().build().unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::MissingPacketDataField(e) if e.field == "sender"
+        ));
+        assert!(err.to_string().contains("sender"), "{}", err);
+
+        let built = builder.clone().with_sender(data.sender.clone()).build();
+        assert_eq!(built.unwrap(), data);
+
+        let zero = PrefixedCoin {
+            amount: Amount::ZERO,
+            ..data.token.clone()
+        };
+        let err = builder
+            .with_sender(data.sender)
+            .with_token(zero)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::ZeroAmount(_)));
+    }
+
+    #[test]
+    fn test_looks_like_ics20() {
+        let data = serde_json::to_vec(&get_dummy_packet_data(Memo::default())).unwrap();
+        assert!(looks_like_ics20(&data));
+        assert!(looks_like_ics20(
+            br#" {"amount":"1","denom":"uatom","receiver":"b","sender":"a"}"#
+        ));
+
+        assert!(!looks_like_ics20(b""));
+        assert!(!looks_like_ics20(&[
+            0x0a, 0x05, 0x75, 0x61, 0x74, 0x6f, 0x6d
+        ]));
+        assert!(!looks_like_ics20(br#"{"result":"AQ=="}"#));
+        assert!(!looks_like_ics20(
+            br#"["denom","amount","sender","receiver"]"#
+        ));
+    }
+
+    #[test]
+    fn test_packet_data_tokens() {
+        let data = get_dummy_packet_data(Memo::default());
+        assert_eq!(data.tokens(), vec![&data.token]);
+    }
+
+    #[test]
+    fn test_is_return_to_source() {
+        // the dummy packet data carries a "transfer/channel-0/uatom" voucher
+        let data = get_dummy_packet_data(Memo::default());
+
+        assert!(data.is_return_to_source(&PortId::transfer(), ChannelId::new(0)));
+        assert!(!data.is_return_to_source(&PortId::transfer(), ChannelId::new(1)));
+
+        let native = PacketData {
+            token: PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            ..data
+        };
+        assert!(!native.is_return_to_source(&PortId::transfer(), ChannelId::new(0)));
+    }
+
+    #[test]
+    fn test_packet_data_validate() {
+        let limits = PacketDataLimits::default();
+        let data = get_dummy_packet_data("hello".parse().unwrap());
+        assert!(data.validate(&limits).is_ok());
+
+        let max_memo = get_dummy_packet_data("a".repeat(limits.max_memo_len).into());
+        assert!(max_memo.validate(&limits).is_ok());
+
+        let long_memo = get_dummy_packet_data("a".repeat(limits.max_memo_len + 1).into());
+        let err = long_memo.validate(&limits).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::PacketDataTooLarge(e) if e.field == "memo" && e.len == limits.max_memo_len + 1
+        ));
+
+        let strict = PacketDataLimits {
+            max_receiver_len: 4,
+            ..limits
+        };
+        let err = data.validate(&strict).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::PacketDataTooLarge(e) if e.field == "receiver"
+        ));
+    }
 }
@@ -1,16 +1,86 @@
 //! This module implements the processing logic for ICS20 (token transfer) message.
-use crate::applications::transfer::context::Ics20Context;
+use alloc::collections::btree_map::BTreeMap;
+
+use crate::applications::transfer::context::{EscrowAddress, Ics20Context};
 use crate::applications::transfer::error::Error as Ics20Error;
-use crate::applications::transfer::is_sender_chain_source;
 use crate::applications::transfer::packet::PacketData;
+use crate::applications::transfer::{is_sender_chain_source, PrefixedCoin};
+use crate::bigint::U256;
 use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::prelude::*;
+use crate::signer::Signer;
 
 pub mod on_ack_packet;
 pub mod on_recv_packet;
 pub mod on_timeout_packet;
 pub mod send_transfer;
 
+/// How the token of a sent packet is returned to its sender if the packet is refunded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefundKind {
+    /// The sender chain is the source of the token, which was escrowed at send time and is
+    /// released from the channel escrow.
+    Unescrow,
+    /// The token is a voucher that was burnt at send time and is minted again.
+    Mint,
+}
+
+/// Returns how the token of `packet` (carrying `data`) is refunded, depending on whether the
+/// sender chain is the source of the token with respect to the packet's source port and channel.
+pub fn refund_kind(packet: &Packet, data: &PacketData) -> RefundKind {
+    if is_sender_chain_source(
+        packet.source_port.clone(),
+        packet.source_channel,
+        &data.token.denom,
+    ) {
+        RefundKind::Unescrow
+    } else {
+        RefundKind::Mint
+    }
+}
+
+/// Returns the account that is credited if `packet` (carrying `data`) is refunded, i.e. its
+/// original sender, along with the coin it receives back. This is the escrowed coin for an
+/// `Unescrow` refund, or the burnt voucher for a `Mint` refund. Which of the two applies to the
+/// packet is returned by `refund_kind`.
+pub fn expected_refund_delta(_packet: &Packet, data: &PacketData) -> (Signer, PrefixedCoin) {
+    // In both cases, the refunded coin has the exact denomination the sender held at send time.
+    (data.sender.clone(), data.token.clone())
+}
+
+/// Returns the signed net token flow (ignoring denominations) of the given sent packets per source
+/// port and channel. Tokens escrowed by the sender chain (i.e. sent from their source) count as
+/// outflow and are added, while vouchers burnt on their way back to their source count as inflow
+/// and are subtracted. Amounts that do not fit in an `i128` saturate.
+pub fn channel_net_flow(packets: &[(Packet, PacketData)]) -> BTreeMap<(PortId, ChannelId), i128> {
+    let max = U256::from(i128::MAX as u128);
+
+    let mut flows = BTreeMap::new();
+    for (packet, data) in packets {
+        let amount = U256::from(data.token.amount);
+        let amount = if amount <= max {
+            amount.as_u128() as i128
+        } else {
+            i128::MAX
+        };
+
+        let flow = flows
+            .entry((packet.source_port.clone(), packet.source_channel))
+            .or_insert(0i128);
+        if is_sender_chain_source(
+            packet.source_port.clone(),
+            packet.source_channel,
+            &data.token.denom,
+        ) {
+            *flow = flow.saturating_add(amount);
+        } else {
+            *flow = flow.saturating_sub(amount);
+        }
+    }
+    flows
+}
+
 fn refund_packet_token(
     ctx: &mut impl Ics20Context,
     packet: &Packet,
@@ -22,19 +92,116 @@ fn refund_packet_token(
         .try_into()
         .map_err(|_| Ics20Error::parse_account_failure())?;
 
-    if is_sender_chain_source(
-        packet.source_port.clone(),
-        packet.source_channel,
-        &data.token.denom,
-    ) {
-        // unescrow tokens back to sender
-        let escrow_address =
-            ctx.get_channel_escrow_address(&packet.source_port, packet.source_channel)?;
+    match refund_kind(packet, data) {
+        // unescrow tokens back to sender from the escrow used at send time
+        RefundKind::Unescrow => {
+            let escrow = EscrowAddress::for_source_of(packet);
+            let escrow_address =
+                ctx.get_channel_escrow_address(escrow.port_id(), escrow.channel_id())?;
+
+            ctx.send_coins(&escrow_address, &sender, &data.token)
+        }
+        // mint vouchers back to sender
+        RefundKind::Mint => ctx.mint_coins(&sender, &data.token),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::transfer::context::test::get_dummy_transfer_module;
+    use crate::applications::transfer::context::Ics20Reader;
+    use crate::test_utils::get_dummy_account_id;
 
-        ctx.send_coins(&escrow_address, &sender, &data.token)
+    fn get_dummy_sent_packet(data: &PacketData) -> Packet {
+        Packet {
+            sequence: 1.into(),
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(0),
+            destination_port: PortId::transfer(),
+            destination_channel: ChannelId::new(1),
+            data: serde_json::to_vec(data).unwrap(),
+            ..Packet::default()
+        }
     }
-    // mint vouchers back to sender
-    else {
-        ctx.mint_coins(&sender, &data.token)
+
+    fn assert_refund_matches_delta(denom: &str, source_channel: u64, expected_kind: RefundKind) {
+        let data = PacketData {
+            token: PrefixedCoin {
+                denom: denom.parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            sender: get_dummy_account_id(),
+            receiver: get_dummy_account_id(),
+            memo: Default::default(),
+        };
+        let packet = Packet {
+            source_channel: ChannelId::new(source_channel),
+            ..get_dummy_sent_packet(&data)
+        };
+
+        let mut ctx = get_dummy_transfer_module();
+        if expected_kind == RefundKind::Unescrow {
+            let escrow_address = ctx
+                .get_channel_escrow_address(&PortId::transfer(), packet.source_channel)
+                .unwrap();
+            ctx = ctx.with_balance(escrow_address, data.token.clone());
+        }
+
+        assert_eq!(refund_kind(&packet, &data), expected_kind);
+        let (account, coin) = expected_refund_delta(&packet, &data);
+        assert_eq!(account, data.sender);
+        assert_eq!(coin, data.token);
+
+        let before = ctx.balance(&account, &coin.denom);
+        refund_packet_token(&mut ctx, &packet, &data).unwrap();
+        let after = ctx.balance(&account, &coin.denom);
+        assert_eq!(after.checked_sub(before), Some(coin.amount));
+    }
+
+    #[test]
+    fn test_expected_refund_delta_escrow() {
+        // the sender chain is the source of `uatom`, so the refund is unescrowed
+        assert_refund_matches_delta("uatom", 0, RefundKind::Unescrow);
+        // a voucher sent onwards over another channel than the one it came in on is escrowed too
+        assert_refund_matches_delta("transfer/channel-0/uatom", 1, RefundKind::Unescrow);
+    }
+
+    #[test]
+    fn test_expected_refund_delta_burn() {
+        // the voucher was burnt when sent back towards its source, so the refund mints it again
+        assert_refund_matches_delta("transfer/channel-0/uatom", 0, RefundKind::Mint);
+    }
+
+    #[test]
+    fn test_channel_net_flow() {
+        let sent = |channel: u64, denom: &str, amount: u64| {
+            let data = PacketData {
+                token: PrefixedCoin {
+                    denom: denom.parse().unwrap(),
+                    amount: amount.into(),
+                },
+                sender: get_dummy_account_id(),
+                receiver: get_dummy_account_id(),
+                memo: Default::default(),
+            };
+            let packet = Packet {
+                source_channel: ChannelId::new(channel),
+                ..get_dummy_sent_packet(&data)
+            };
+            (packet, data)
+        };
+
+        let flows = channel_net_flow(&[
+            // escrowed
+            sent(0, "uatom", 10),
+            // burnt voucher returning over the channel it came in on
+            sent(0, "transfer/channel-0/uosmo", 4),
+            sent(1, "uatom", 7),
+        ]);
+
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows[&(PortId::transfer(), ChannelId::new(0))], 6);
+        assert_eq!(flows[&(PortId::transfer(), ChannelId::new(1))], 7);
     }
 }
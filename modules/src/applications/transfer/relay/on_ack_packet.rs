@@ -5,6 +5,9 @@ use crate::applications::transfer::packet::PacketData;
 use crate::applications::transfer::relay::refund_packet_token;
 use crate::core::ics04_channel::packet::Packet;
 
+/// Processes the acknowledgement of a transfer packet: an error acknowledgement refunds the sender
+/// exactly as a timeout would, while a success acknowledgement leaves all balances untouched (the
+/// ack events are emitted by `on_acknowledgement_packet` in both cases).
 pub fn process_ack_packet(
     ctx: &mut impl Ics20Context,
     packet: &Packet,
@@ -17,3 +20,74 @@ pub fn process_ack_packet(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::transfer::acknowledgement::ACK_ERR_STR;
+    use crate::applications::transfer::context::test::get_dummy_transfer_module;
+    use crate::applications::transfer::context::Ics20Reader;
+    use crate::applications::transfer::PrefixedCoin;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::prelude::*;
+    use crate::test_utils::{get_dummy_account_id, DummyTransferModule};
+
+    fn get_dummy_acked_packet() -> (Packet, PacketData) {
+        let data = PacketData {
+            token: PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            sender: get_dummy_account_id(),
+            receiver: get_dummy_account_id(),
+            memo: Default::default(),
+        };
+        let packet = Packet {
+            sequence: 1.into(),
+            source_port: PortId::transfer(),
+            source_channel: ChannelId::new(0),
+            destination_port: PortId::transfer(),
+            destination_channel: ChannelId::new(1),
+            data: serde_json::to_vec(&data).unwrap(),
+            ..Packet::default()
+        };
+        (packet, data)
+    }
+
+    fn get_escrowing_transfer_module(data: &PacketData) -> DummyTransferModule {
+        let ctx = get_dummy_transfer_module();
+        let escrow_address = ctx
+            .get_channel_escrow_address(&PortId::transfer(), ChannelId::new(0))
+            .unwrap();
+        ctx.with_balance(escrow_address, data.token.clone())
+    }
+
+    #[test]
+    fn test_success_ack_leaves_balances_unchanged() {
+        let (packet, data) = get_dummy_acked_packet();
+        let mut ctx = get_escrowing_transfer_module(&data);
+
+        let ack = Acknowledgement::try_from(br#"{"result":"AQ=="}"#.to_vec()).unwrap();
+        assert!(matches!(ack, Acknowledgement::Success(_)));
+
+        let before = ctx.balance(&data.sender, &data.token.denom);
+        process_ack_packet(&mut ctx, &packet, &data, &ack).unwrap();
+        assert_eq!(ctx.balance(&data.sender, &data.token.denom), before);
+    }
+
+    #[test]
+    fn test_error_ack_refunds_sender() {
+        let (packet, data) = get_dummy_acked_packet();
+        let mut ctx = get_escrowing_transfer_module(&data);
+
+        let ack =
+            Acknowledgement::try_from(format!(r#"{{"error":"{}"}}"#, ACK_ERR_STR).into_bytes())
+                .unwrap();
+        assert!(matches!(ack, Acknowledgement::Error(_)));
+
+        let before = ctx.balance(&data.sender, &data.token.denom);
+        process_ack_packet(&mut ctx, &packet, &data, &ack).unwrap();
+        let after = ctx.balance(&data.sender, &data.token.denom);
+        assert_eq!(after.checked_sub(before), Some(data.token.amount));
+    }
+}
@@ -1,8 +1,11 @@
-use crate::applications::transfer::context::Ics20Context;
+use core::any::{type_name, Any};
+
+use crate::applications::transfer::context::{EscrowAddress, Ics20Context};
 use crate::applications::transfer::error::Error as Ics20Error;
-use crate::applications::transfer::events::DenomTraceEvent;
-use crate::applications::transfer::packet::PacketData;
-use crate::applications::transfer::{is_receiver_chain_source, TracePrefix};
+use crate::applications::transfer::events::{DenomTraceEvent, RecvAction, RecvTransferEvent};
+use crate::applications::transfer::packet::{ForwardMemo, PacketData};
+use crate::applications::transfer::{receiver_denom, PrefixedCoin, Source, TracePrefix};
+use crate::bigint::U256;
 use crate::core::ics04_channel::packet::Packet;
 use crate::core::ics26_routing::context::{ModuleOutputBuilder, WriteFn};
 use crate::prelude::*;
@@ -13,36 +16,93 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
     packet: &Packet,
     data: PacketData,
 ) -> Result<Box<WriteFn>, Ics20Error> {
-    if !ctx.is_receive_enabled() {
+    // like blocked denominations, disabled ones refer to the denomination credited on this chain
+    let local_denom = receiver_denom(
+        &packet.source_port,
+        &packet.source_channel,
+        &packet.destination_port,
+        &packet.destination_channel,
+        &data.token.denom,
+    );
+    if !ctx.is_receive_enabled_for(&local_denom) {
         return Err(Ics20Error::receive_disabled());
     }
 
-    let receiver_account = data
-        .receiver
+    data.validate(&ctx.packet_data_limits())?;
+
+    let channel_end = ctx
+        .channel_end(&(packet.destination_port.clone(), packet.destination_channel))
+        .map_err(Ics20Error::ics04_channel)?;
+    if !channel_end.is_open() {
+        return Err(Ics20Error::channel_closed(packet.destination_channel));
+    }
+
+    // a zero amount would be a no-op credit that still emits events
+    if data.token.amount.is_zero() {
+        return Err(Ics20Error::zero_amount());
+    }
+
+    if let Some(excess_decimals) = ctx.excess_recv_decimals(&data.token.denom) {
+        let amount = U256::from(data.token.amount);
+        let loses_precision = match U256::from(10).checked_pow_u32(excess_decimals.into()) {
+            Some(unit) => !(amount % unit).is_zero(),
+            // any non-zero amount is smaller than the unit
+            None => !amount.is_zero(),
+        };
+        if loses_precision {
+            return Err(Ics20Error::amount_precision_loss(
+                data.token.amount,
+                excess_decimals,
+            ));
+        }
+    }
+
+    let forward = data.memo.forward();
+
+    let receiver = ctx.translate_receiver(data.receiver.as_ref())?;
+    let receiver_account = receiver
         .clone()
         .try_into()
         .map_err(|_| Ics20Error::parse_account_failure())?;
 
-    if is_receiver_chain_source(
-        packet.source_port.clone(),
-        packet.source_channel,
-        &data.token.denom,
-    ) {
+    let source_prefix = TracePrefix::new(packet.source_port.clone(), packet.source_channel);
+    if data.token.denom.source_chain(&source_prefix) == Source::Receiver {
         // sender chain is not the source, unescrow tokens
-        let prefix = TracePrefix::new(packet.source_port.clone(), packet.source_channel);
         let coin = {
             let mut c = data.token;
-            c.denom.remove_trace_prefix(&prefix);
+            c.denom.remove_trace_prefix(&source_prefix);
             c
         };
 
+        if ctx.is_blocked_denom(&coin.denom) {
+            return Err(Ics20Error::denom_not_allowed(coin.denom));
+        }
+
+        let escrow = EscrowAddress::for_destination_of(packet);
         let escrow_address =
-            ctx.get_channel_escrow_address(&packet.destination_port, packet.destination_channel)?;
+            ctx.get_channel_escrow_address(escrow.port_id(), escrow.channel_id())?;
+
+        if let Some(escrowed) =
+            ctx.channel_escrow_balance(escrow.port_id(), escrow.channel_id(), &coin.denom)
+        {
+            if escrowed < coin.amount {
+                return Err(Ics20Error::insufficient_escrow(coin, escrowed));
+            }
+        }
+
+        let transfer_event = RecvTransferEvent {
+            sender: data.sender,
+            receiver,
+            coin: coin.clone(),
+            action: RecvAction::Unescrow,
+        };
+        output.emit(transfer_event.into());
 
         Ok(Box::new(move |ctx| {
-            let ctx = ctx.downcast_mut::<Ctx>().unwrap();
+            let ctx = downcast_ctx::<Ctx>(ctx)?;
             ctx.send_coins(&escrow_address, &receiver_account, &coin)
-                .map_err(|e| e.to_string())
+                .map_err(|e| e.to_string())?;
+            forward_received(ctx, forward.as_ref(), &coin)
         }))
     } else {
         // sender chain is the source, mint vouchers
@@ -53,16 +113,509 @@ pub fn process_recv_packet<Ctx: 'static + Ics20Context>(
             c
         };
 
+        if ctx.is_blocked_denom(&coin.denom) {
+            return Err(Ics20Error::denom_not_allowed(coin.denom));
+        }
+
+        if let Some(known) = ctx.known_trace_prefixes() {
+            if !coin.denom.trace_path().all_known(&known) {
+                return Err(Ics20Error::unknown_trace_prefix(coin.denom));
+            }
+        }
+
+        let supply = ctx.voucher_supply(&coin.denom);
+        let exceeds_cap = match supply.checked_add(coin.amount) {
+            Some(new_supply) => {
+                matches!(ctx.voucher_supply_cap(&coin.denom), Some(cap) if new_supply > cap)
+            }
+            None => true,
+        };
+        if exceeds_cap {
+            return Err(Ics20Error::supply_overflow(coin, supply));
+        }
+
         let denom_trace_event = DenomTraceEvent {
             trace_hash: ctx.denom_hash_string(&coin.denom),
             denom: coin.denom.clone(),
         };
         output.emit(denom_trace_event.into());
 
+        let transfer_event = RecvTransferEvent {
+            sender: data.sender,
+            receiver,
+            coin: coin.clone(),
+            action: RecvAction::Mint,
+        };
+        output.emit(transfer_event.into());
+
         Ok(Box::new(move |ctx| {
-            let ctx = ctx.downcast_mut::<Ctx>().unwrap();
+            let ctx = downcast_ctx::<Ctx>(ctx)?;
+            // record the trace of first-time voucher denoms so that their hash can be resolved
+            if !ctx.has_denom_trace(&coin.denom.hashed()) {
+                ctx.set_denom_trace(&coin.denom)
+                    .map_err(|e| e.to_string())?;
+            }
             ctx.mint_coins(&receiver_account, &coin)
-                .map_err(|e| e.to_string())
+                .map_err(|e| e.to_string())?;
+            forward_received(ctx, forward.as_ref(), &coin)
         }))
     }
 }
+
+/// Initiates the onward transfer of the received `coin` if the packet memo carried a forwarding
+/// instruction.
+fn forward_received<Ctx: Ics20Context>(
+    ctx: &mut Ctx,
+    forward: Option<&ForwardMemo>,
+    coin: &PrefixedCoin,
+) -> Result<(), String> {
+    match forward {
+        Some(instruction) => ctx
+            .forward_packet(instruction, coin)
+            .map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Downcasts the context passed to a `WriteFn` to the ICS20 context the callback was built for.
+fn downcast_ctx<Ctx: 'static>(ctx: &mut dyn Any) -> Result<&mut Ctx, String> {
+    // The name of the concrete type behind `dyn Any` is not available, only its `TypeId`.
+    let actual = Any::type_id(&*ctx);
+    ctx.downcast_mut::<Ctx>().ok_or_else(|| {
+        format!(
+            "ICS20 write callback received a mismatched context: expected `{}`, got {:?}",
+            type_name::<Ctx>(),
+            actual
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::transfer::context::test::{
+        get_dummy_recv_packet, get_dummy_transfer_module, get_dummy_transfer_module_in_state,
+    };
+    use crate::applications::transfer::context::Ics20Reader;
+    use crate::applications::transfer::error::ErrorDetail;
+    use crate::applications::transfer::{Amount, BaseCoin, PrefixedDenom};
+    use crate::core::ics04_channel::channel::State;
+    use crate::core::ics24_host::identifier::{ChannelId, PortId};
+    use crate::handler::HandlerOutputBuilder;
+    use crate::signer::Signer;
+    use crate::test_utils::{get_dummy_account_id, DummyTransferModule};
+
+    fn get_dummy_packet_data() -> PacketData {
+        PacketData {
+            token: BaseCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            }
+            .into(),
+            sender: get_dummy_account_id(),
+            receiver: get_dummy_account_id(),
+            memo: Default::default(),
+        }
+    }
+
+    /// Returns a transfer module whose escrow account for the destination channel of
+    /// `get_dummy_recv_packet` holds the given amount of "uatom".
+    fn get_dummy_transfer_module_with_escrow(amount: u64) -> DummyTransferModule {
+        let ctx = get_dummy_transfer_module();
+        let escrow_address = ctx
+            .get_channel_escrow_address(&PortId::transfer(), ChannelId::new(0))
+            .unwrap();
+        let escrowed = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: amount.into(),
+        };
+        ctx.with_balance(escrow_address, escrowed)
+    }
+
+    #[test]
+    fn test_write_fn_context_mismatch() {
+        let ctx = get_dummy_transfer_module();
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+
+        let write_fn =
+            process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).unwrap();
+
+        let err = write_fn(&mut ()).unwrap_err();
+        assert!(err.contains("mismatched context"), "{}", err);
+        assert!(err.contains(type_name::<DummyTransferModule>()), "{}", err);
+    }
+
+    #[test]
+    fn test_translate_receiver() {
+        let hex_address = "0x0cda3f47ef3c4906693b170ef650eb968c5f4b2c";
+        let receiver: Signer = "cosmos1pndr73l083ysv6fmzu80v58tj6x97jevhzatht"
+            .parse()
+            .unwrap();
+
+        let mut ctx =
+            get_dummy_transfer_module().with_receiver_translation(hex_address, receiver.clone());
+        let data = PacketData {
+            receiver: hex_address.parse().unwrap(),
+            ..get_dummy_packet_data()
+        };
+        let packet = get_dummy_recv_packet(&data);
+
+        let write_fn =
+            process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).unwrap();
+        write_fn(&mut ctx).unwrap();
+
+        let voucher_denom = "transfer/channel-0/uatom".parse().unwrap();
+        assert_eq!(ctx.balance(&receiver, &voucher_denom), 10u64.into());
+    }
+
+    #[test]
+    fn test_recv_on_closed_channel() {
+        let ctx = get_dummy_transfer_module_in_state(State::Closed);
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+
+        let res = process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data);
+        assert!(matches!(
+            res.map(|_| ()).unwrap_err().detail(),
+            ErrorDetail::ChannelClosed(_)
+        ));
+    }
+
+    #[test]
+    fn test_recv_zero_amount() {
+        let ctx = get_dummy_transfer_module();
+
+        for denom in ["uatom", "transfer/channel-1/uatom"] {
+            let data = PacketData {
+                token: PrefixedCoin {
+                    denom: denom.parse().unwrap(),
+                    amount: Amount::ZERO,
+                },
+                ..get_dummy_packet_data()
+            };
+            let packet = get_dummy_recv_packet(&data);
+
+            let mut output = HandlerOutputBuilder::new();
+            let res = process_recv_packet(&ctx, &mut output, &packet, data);
+            assert!(matches!(
+                res.map(|_| ()).unwrap_err().detail(),
+                ErrorDetail::ZeroAmount(_)
+            ));
+            assert!(output.with_result(()).events.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_recv_transfer_event() {
+        let ctx = get_dummy_transfer_module_with_escrow(10);
+
+        for (denom, action, credited_denom) in [
+            ("uatom", "mint", "transfer/channel-0/uatom"),
+            ("transfer/channel-1/uatom", "unescrow", "uatom"),
+        ] {
+            let data = PacketData {
+                token: PrefixedCoin {
+                    denom: denom.parse().unwrap(),
+                    amount: 10u64.into(),
+                },
+                ..get_dummy_packet_data()
+            };
+            let packet = get_dummy_recv_packet(&data);
+
+            let mut output = HandlerOutputBuilder::new();
+            process_recv_packet(&ctx, &mut output, &packet, data).unwrap();
+
+            let events = output.with_result(()).events;
+            let transfer_event = events
+                .iter()
+                .find(|event| event.kind == "ibc_recv_transfer")
+                .expect("missing transfer event");
+            let attribute = |key: &str| {
+                transfer_event
+                    .attributes
+                    .iter()
+                    .find(|attr| attr.key == key)
+                    .map(|attr| attr.value.as_str())
+            };
+            assert_eq!(attribute("action"), Some(action));
+            assert_eq!(attribute("denom"), Some(credited_denom));
+            assert_eq!(attribute("amount"), Some("10"));
+
+            // the denom trace event is still emitted when minting
+            assert_eq!(
+                events
+                    .iter()
+                    .any(|event| event.kind == "denomination_trace"),
+                action == "mint"
+            );
+        }
+    }
+
+    #[test]
+    fn test_recv_stores_novel_denom_trace() {
+        let mut ctx = get_dummy_transfer_module();
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+
+        let voucher_denom: PrefixedDenom = "transfer/channel-0/uatom".parse().unwrap();
+        assert!(!ctx.has_denom_trace(&voucher_denom.hashed()));
+
+        let write_fn =
+            process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).unwrap();
+        write_fn(&mut ctx).unwrap();
+
+        assert_eq!(
+            ctx.get_denom_trace(&voucher_denom.hashed()),
+            Some(voucher_denom)
+        );
+    }
+
+    #[test]
+    fn test_recv_strict_trace_prefixes() {
+        let known = [TracePrefix::new(PortId::transfer(), ChannelId::new(0))]
+            .into_iter()
+            .collect();
+        let ctx = get_dummy_transfer_module().with_known_trace_prefixes(known);
+
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+        assert!(process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).is_ok());
+
+        // the voucher would be "transfer/channel-0/transfer/channel-5/uatom", with an unknown hop
+        let data = PacketData {
+            token: PrefixedCoin {
+                denom: "transfer/channel-5/uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            ..get_dummy_packet_data()
+        };
+        let packet = get_dummy_recv_packet(&data);
+        let res = process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data);
+        assert!(matches!(
+            res.map(|_| ()).unwrap_err().detail(),
+            ErrorDetail::UnknownTracePrefix(_)
+        ));
+    }
+
+    #[test]
+    fn test_recv_amount_precision_loss() {
+        let ctx = get_dummy_transfer_module().with_excess_recv_decimals(3);
+
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+        let res = process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data);
+        assert!(matches!(
+            res.map(|_| ()).unwrap_err().detail(),
+            ErrorDetail::AmountPrecisionLoss(_)
+        ));
+
+        let data = PacketData {
+            token: BaseCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 5000u64.into(),
+            }
+            .into(),
+            ..get_dummy_packet_data()
+        };
+        let packet = get_dummy_recv_packet(&data);
+        assert!(process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).is_ok());
+    }
+
+    #[test]
+    fn test_recv_forward_memo() {
+        let mut ctx = get_dummy_transfer_module();
+        let data = PacketData {
+            memo: r#"{"forward":{"receiver":"cosmos1pndr73l083ysv6fmzu80v58tj6x97jevhzatht","port":"transfer","channel":"channel-5"}}"#
+                .parse()
+                .unwrap(),
+            ..get_dummy_packet_data()
+        };
+        let packet = get_dummy_recv_packet(&data);
+
+        let write_fn =
+            process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).unwrap();
+        write_fn(&mut ctx).unwrap();
+
+        let voucher = PrefixedCoin {
+            denom: "transfer/channel-0/uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let instruction = ForwardMemo {
+            receiver: "cosmos1pndr73l083ysv6fmzu80v58tj6x97jevhzatht"
+                .parse()
+                .unwrap(),
+            port: PortId::transfer(),
+            channel: ChannelId::new(5),
+        };
+        assert_eq!(ctx.forwarded(), [(instruction, voucher)]);
+
+        // no forwarding without a forwarding memo
+        let mut ctx = get_dummy_transfer_module();
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+        let write_fn =
+            process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).unwrap();
+        write_fn(&mut ctx).unwrap();
+        assert!(ctx.forwarded().is_empty());
+    }
+
+    #[test]
+    fn test_recv_native_token_unescrows_exact_amount() {
+        let data = PacketData {
+            token: PrefixedCoin {
+                denom: "transfer/channel-1/uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            ..get_dummy_packet_data()
+        };
+        let packet = get_dummy_recv_packet(&data);
+        let uatom: PrefixedDenom = "uatom".parse().unwrap();
+
+        // unescrowing more than escrowed is rejected
+        let ctx = get_dummy_transfer_module_with_escrow(5);
+        let res = process_recv_packet(
+            &ctx,
+            &mut HandlerOutputBuilder::new(),
+            &packet,
+            data.clone(),
+        );
+        assert!(matches!(
+            res.map(|_| ()).unwrap_err().detail(),
+            ErrorDetail::InsufficientEscrow(_)
+        ));
+
+        let mut ctx = get_dummy_transfer_module_with_escrow(15);
+        let write_fn = process_recv_packet(
+            &ctx,
+            &mut HandlerOutputBuilder::new(),
+            &packet,
+            data.clone(),
+        )
+        .unwrap();
+        write_fn(&mut ctx).unwrap();
+
+        let escrow_address = ctx
+            .get_channel_escrow_address(&PortId::transfer(), ChannelId::new(0))
+            .unwrap();
+        assert_eq!(ctx.balance(&data.receiver, &uatom), 10u64.into());
+        assert_eq!(ctx.balance(&escrow_address, &uatom), 5u64.into());
+    }
+
+    #[test]
+    fn test_recv_blocked_denom() {
+        // the blocked denomination is the voucher, not the denomination in the packet
+        let ctx = get_dummy_transfer_module_with_escrow(10)
+            .with_blocked_denom("transfer/channel-0/uatom".parse().unwrap())
+            .with_blocked_denom("uatom".parse().unwrap());
+
+        for denom in ["uatom", "transfer/channel-1/uatom"] {
+            let data = PacketData {
+                token: PrefixedCoin {
+                    denom: denom.parse().unwrap(),
+                    amount: 10u64.into(),
+                },
+                ..get_dummy_packet_data()
+            };
+            let packet = get_dummy_recv_packet(&data);
+            let res = process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data);
+            assert!(matches!(
+                res.map(|_| ()).unwrap_err().detail(),
+                ErrorDetail::DenomNotAllowed(_)
+            ));
+        }
+
+        // the voucher of another channel is not blocked
+        let ctx = get_dummy_transfer_module()
+            .with_blocked_denom("transfer/channel-5/uatom".parse().unwrap());
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+        assert!(process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).is_ok());
+    }
+
+    #[test]
+    fn test_recv_disabled_denom() {
+        // the foreign `uatom` is minted as `transfer/channel-0/uatom`, which is disabled
+        let ctx = get_dummy_transfer_module()
+            .with_disabled_denom("transfer/channel-0/uatom".parse().unwrap());
+
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+        let res = process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data);
+        assert!(matches!(
+            res.map(|_| ()).unwrap_err().detail(),
+            ErrorDetail::ReceiveDisabled(_)
+        ));
+
+        let data = PacketData {
+            token: PrefixedCoin {
+                denom: "uosmo".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            ..get_dummy_packet_data()
+        };
+        let packet = get_dummy_recv_packet(&data);
+        assert!(process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).is_ok());
+    }
+
+    #[test]
+    fn test_recv_disabled_native_denom() {
+        let ctx =
+            get_dummy_transfer_module_with_escrow(10).with_disabled_denom("uatom".parse().unwrap());
+
+        // the chain's own `uatom` returning over channel-0 is unescrowed as `uatom`, so it is
+        // disabled even though the packet carries `transfer/channel-1/uatom`
+        let data = PacketData {
+            token: PrefixedCoin {
+                denom: "transfer/channel-1/uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            ..get_dummy_packet_data()
+        };
+        let packet = get_dummy_recv_packet(&data);
+        let res = process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data);
+        assert!(matches!(
+            res.map(|_| ()).unwrap_err().detail(),
+            ErrorDetail::ReceiveDisabled(_)
+        ));
+
+        // a foreign `uatom` is minted as `transfer/channel-0/uatom`, which is not disabled
+        let data = get_dummy_packet_data();
+        let packet = get_dummy_recv_packet(&data);
+        assert!(process_recv_packet(&ctx, &mut HandlerOutputBuilder::new(), &packet, data).is_ok());
+    }
+
+    #[test]
+    fn test_recv_voucher_supply_overflow() {
+        let voucher_denom: PrefixedDenom = "transfer/channel-0/uatom".parse().unwrap();
+        let holder: Signer = "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a"
+            .parse()
+            .unwrap();
+        let with_supply = |supply: U256| {
+            get_dummy_transfer_module().with_balance(
+                holder.clone(),
+                PrefixedCoin {
+                    denom: voucher_denom.clone(),
+                    amount: supply.into(),
+                },
+            )
+        };
+        // receives 10 uatom, minted as `voucher_denom`
+        let recv = |ctx: &DummyTransferModule| {
+            let data = get_dummy_packet_data();
+            let packet = get_dummy_recv_packet(&data);
+            process_recv_packet(ctx, &mut HandlerOutputBuilder::new(), &packet, data).map(|_| ())
+        };
+
+        assert!(recv(&with_supply(U256::MAX - 10)).is_ok());
+        let err = recv(&with_supply(U256::MAX - 9)).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::SupplyOverflow(_)));
+
+        let capped = |cap: u64| {
+            with_supply(U256::from(5u64)).with_voucher_supply_cap(voucher_denom.clone(), cap.into())
+        };
+        assert!(recv(&capped(15)).is_ok());
+        let err = recv(&capped(14)).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::SupplyOverflow(_)));
+    }
+}
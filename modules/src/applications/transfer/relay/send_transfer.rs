@@ -3,29 +3,84 @@ use crate::applications::transfer::error::Error;
 use crate::applications::transfer::events::TransferEvent;
 use crate::applications::transfer::msgs::transfer::MsgTransfer;
 use crate::applications::transfer::packet::PacketData;
-use crate::applications::transfer::{is_sender_chain_source, Coin, PrefixedCoin};
+use crate::applications::transfer::{
+    is_sender_chain_source, Amount, Coin, PrefixedCoin, TracePrefix,
+};
+use crate::core::ics04_channel::commitment::PacketCommitment;
 use crate::core::ics04_channel::handler::send_packet::send_packet;
-use crate::core::ics04_channel::packet::Packet;
+use crate::core::ics04_channel::packet::{Packet, Sequence};
+use crate::core::ics24_host::identifier::{ChannelId, PortId};
 use crate::events::ModuleEvent;
 use crate::handler::{HandlerOutput, HandlerOutputBuilder};
 use crate::prelude::*;
+use crate::signer::Signer;
+use crate::timestamp::Timestamp;
 
-/// This function handles the transfer sending logic.
+/// This function handles the transfer sending logic and returns the commitment of the sent packet,
+/// i.e. the commitment a relayer expects to find in the store for it.
 /// If this method returns an error, the runtime is expected to rollback all state modifications to
 /// the `Ctx` caused by all messages from the transaction that this `msg` is a part of.
 pub fn send_transfer<Ctx, C>(
     ctx: &mut Ctx,
     output: &mut HandlerOutputBuilder<()>,
     msg: MsgTransfer<C>,
-) -> Result<(), Error>
+) -> Result<PacketCommitment, Error>
+where
+    Ctx: Ics20Context,
+    C: TryInto<PrefixedCoin>,
+    Error: From<C::Error>,
+{
+    send_transfer_with_fee(ctx, output, msg, None)
+}
+
+/// Same as `send_transfer`, but if a `fee` is given, its amount (in the denomination of the
+/// transferred token) is first sent from the sender to the fee collector and only the remainder is
+/// escrowed or burnt and carried by the packet.
+/// Fails with `Error::invalid_fee` if the fee is not less than the transferred amount.
+pub fn send_transfer_with_fee<Ctx, C>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutputBuilder<()>,
+    msg: MsgTransfer<C>,
+    fee: Option<(Signer, Amount)>,
+) -> Result<PacketCommitment, Error>
 where
     Ctx: Ics20Context,
     C: TryInto<PrefixedCoin>,
+    Error: From<C::Error>,
 {
-    if !ctx.is_send_enabled() {
+    let mut token: PrefixedCoin = msg.token.try_into()?;
+    if !ctx.is_send_enabled_for(&token.denom) {
         return Err(Error::send_disabled());
     }
 
+    if ctx.is_forbidden_recipient(&msg.receiver) {
+        return Err(Error::forbidden_recipient(msg.receiver));
+    }
+
+    if msg.timeout_height.is_zero() && msg.timeout_timestamp == Timestamp::none() {
+        return Err(Error::invalid_timeout(
+            "timeout height and timeout timestamp cannot both be disabled".to_string(),
+        ));
+    }
+    let host_height = ctx.host_height();
+    if !msg.timeout_height.is_zero() && msg.timeout_height <= host_height {
+        return Err(Error::invalid_timeout(format!(
+            "timeout height {} is not after the host height {}",
+            msg.timeout_height, host_height
+        )));
+    }
+    // a host that does not know its own timestamp cannot check the timeout timestamp
+    let host_timestamp = ctx.host_timestamp();
+    if msg.timeout_timestamp != Timestamp::none()
+        && host_timestamp != Timestamp::none()
+        && !msg.timeout_timestamp.after(&host_timestamp)
+    {
+        return Err(Error::invalid_timeout(format!(
+            "timeout timestamp {} is not after the host timestamp {}",
+            msg.timeout_timestamp, host_timestamp
+        )));
+    }
+
     let source_channel_end = ctx
         .channel_end(&(msg.source_port.clone(), msg.source_channel))
         .map_err(Error::ics04_channel)?;
@@ -43,35 +98,78 @@ where
         .get_next_sequence_send(&(msg.source_port.clone(), msg.source_channel))
         .map_err(Error::ics04_channel)?;
 
-    let token = msg.token.try_into().map_err(|_| Error::invalid_token())?;
+    if token.amount.is_zero() {
+        return Err(Error::zero_amount());
+    }
+
+    let fee = match fee {
+        Some((fee_collector, fee)) => {
+            let net_amount = token
+                .amount
+                .checked_sub(fee)
+                .filter(|net_amount| !net_amount.is_zero())
+                .ok_or_else(|| Error::invalid_fee(fee, token.amount))?;
+            let fee_collector: <Ctx as Ics20Context>::AccountId = fee_collector
+                .try_into()
+                .map_err(|_| Error::parse_account_failure())?;
+            let fee = Coin {
+                denom: token.denom.clone(),
+                amount: fee,
+            };
+            token.amount = net_amount;
+            Some((fee_collector, fee))
+        }
+        None => None,
+    };
+
+    if ctx.is_counterparty_u128_bounded(&msg.source_port, msg.source_channel) && !token.fits_u128()
+    {
+        return Err(Error::amount_exceeds_u128(token.amount));
+    }
+
+    if token.denom.has_hashed_base() {
+        if ctx.is_strict_denom_check() {
+            return Err(Error::hashed_base_denom(token.denom));
+        }
+        output.log(format!(
+            "warning: base denomination of {} is an unresolved IBC hash",
+            token.denom
+        ));
+    }
+
     let denom = token.denom.clone();
     let coin = Coin {
         denom: denom.clone(),
         amount: token.amount,
     };
 
+    let data = PacketData {
+        token: coin.clone(),
+        sender: msg.sender.clone(),
+        receiver: msg.receiver.clone(),
+        memo: msg.memo,
+    };
+    data.validate(&ctx.packet_data_limits())?;
+
     let sender = msg
         .sender
         .clone()
         .try_into()
         .map_err(|_| Error::parse_account_failure())?;
 
+    if let Some((fee_collector, fee)) = &fee {
+        ctx.send_coins(&sender, fee_collector, fee)?;
+    }
+
     if is_sender_chain_source(msg.source_port.clone(), msg.source_channel, &denom) {
         let escrow_address =
             ctx.get_channel_escrow_address(&msg.source_port, msg.source_channel)?;
         ctx.send_coins(&sender, &escrow_address, &coin)?;
     } else {
-        ctx.burn_coins(&sender, &coin)?;
+        burn_vouchers(ctx, &sender, &coin, &msg.source_port, msg.source_channel)?;
     }
 
-    let data = {
-        let data = PacketData {
-            token: coin,
-            sender: msg.sender.clone(),
-            receiver: msg.receiver.clone(),
-        };
-        serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
-    };
+    let data = serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed");
 
     let packet = Packet {
         sequence,
@@ -84,6 +182,12 @@ where
         timeout_timestamp: msg.timeout_timestamp,
     };
 
+    let commitment = ctx.packet_commitment(
+        packet.data.clone(),
+        packet.timeout_height,
+        packet.timeout_timestamp,
+    );
+
     let HandlerOutput {
         result,
         log,
@@ -108,8 +212,594 @@ where
     let transfer_event = TransferEvent {
         sender: msg.sender,
         receiver: msg.receiver,
+        coin: token,
+        sequence,
     };
     output.emit(ModuleEvent::from(transfer_event).into());
 
-    Ok(())
+    Ok(commitment)
+}
+
+/// Sends each of the coins in `msg` in a separate packet, all sharing the sender, receiver, timeout
+/// and memo of `msg`, and returns the sequences of the packets in the same order as the coins.
+/// Fails if no coins are given or if any of them has a zero amount.
+/// As with `send_transfer`, the runtime is expected to rollback all state modifications to the
+/// `Ctx` if this function returns an error.
+pub fn send_transfers<Ctx>(
+    ctx: &mut Ctx,
+    output: &mut HandlerOutputBuilder<()>,
+    msg: MsgTransfer<Vec<PrefixedCoin>>,
+) -> Result<Vec<Sequence>, Error>
+where
+    Ctx: Ics20Context,
+{
+    if msg.token.is_empty() {
+        return Err(Error::empty_transfer());
+    }
+    if msg.token.iter().any(|coin| coin.amount.is_zero()) {
+        return Err(Error::zero_amount());
+    }
+
+    let mut sequences = Vec::with_capacity(msg.token.len());
+    for coin in msg.token {
+        let sequence = ctx
+            .get_next_sequence_send(&(msg.source_port.clone(), msg.source_channel))
+            .map_err(Error::ics04_channel)?;
+
+        let coin_msg = MsgTransfer {
+            source_port: msg.source_port.clone(),
+            source_channel: msg.source_channel,
+            token: coin,
+            sender: msg.sender.clone(),
+            receiver: msg.receiver.clone(),
+            timeout_height: msg.timeout_height,
+            timeout_timestamp: msg.timeout_timestamp,
+            memo: msg.memo.clone(),
+        };
+        send_transfer(ctx, output, coin_msg)?;
+
+        sequences.push(sequence);
+    }
+
+    Ok(sequences)
+}
+
+/// Burns the `voucher` held by `sender`, as done by `send_transfer` when the sender chain is not
+/// the source of the token. Fails with `Error::insufficient_funds` if `sender` holds less than the
+/// voucher amount, in which case no state is modified.
+///
+/// The voucher is being sent back towards its source, so the outermost prefix of its denomination
+/// must be the `source_port` and `source_channel` it is sent over, otherwise this fails with
+/// `Error::mismatched_source_channel`.
+pub fn burn_vouchers<Ctx>(
+    ctx: &mut Ctx,
+    sender: &<Ctx as Ics20Context>::AccountId,
+    voucher: &PrefixedCoin,
+    source_port: &PortId,
+    source_channel: ChannelId,
+) -> Result<(), Error>
+where
+    Ctx: Ics20Context,
+{
+    let source_prefix = TracePrefix::new(source_port.clone(), source_channel);
+    if voucher.denom.return_route().as_ref() != Some(&source_prefix) {
+        return Err(Error::mismatched_source_channel(
+            voucher.denom.clone(),
+            source_port.clone(),
+            source_channel,
+        ));
+    }
+
+    ctx.burn_coins(sender, voucher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::applications::transfer::context::test::{
+        get_dummy_msg_transfer_of, get_dummy_transfer_module,
+    };
+    use crate::applications::transfer::error::ErrorDetail;
+    use crate::applications::transfer::BaseDenom;
+    use crate::bigint::U256;
+    use crate::core::ics02_client::height::Height;
+    use crate::core::ics04_channel::context::ChannelReader;
+    use crate::events::IbcEvent;
+    use crate::mock::client_state::MockConsensusState;
+    use crate::mock::header::MockHeader;
+    use crate::test_utils::DummyTransferModule;
+
+    #[test]
+    fn test_send_transfer_u128_bounded_counterparty() {
+        let fitting = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: U256::from(u128::MAX).into(),
+        };
+        let overflowing = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: (U256::from(u128::MAX) + U256::one()).into(),
+        };
+        assert!(fitting.fits_u128());
+        assert!(!overflowing.fits_u128());
+
+        let sender = get_dummy_msg_transfer_of(fitting.clone()).sender;
+        let mut ctx = get_dummy_transfer_module()
+            .with_u128_bounded_counterparty()
+            .with_balance(sender, overflowing.clone());
+
+        let err = send_transfer(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            get_dummy_msg_transfer_of(overflowing),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::AmountExceedsU128(_)));
+
+        let res = send_transfer(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            get_dummy_msg_transfer_of(fitting),
+        );
+        assert!(res.is_ok(), "{:?}", res);
+    }
+
+    #[test]
+    fn test_send_transfer_memo() {
+        let token = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let msg = MsgTransfer {
+            memo: "forward to osmosis".parse().unwrap(),
+            ..get_dummy_msg_transfer_of(token.clone())
+        };
+        let mut ctx = get_dummy_transfer_module().with_balance(msg.sender.clone(), token);
+
+        let mut output = HandlerOutputBuilder::new();
+        send_transfer(&mut ctx, &mut output, msg.clone()).unwrap();
+
+        let packet_data = output
+            .with_result(())
+            .events
+            .into_iter()
+            .find_map(|event| match event {
+                IbcEvent::SendPacket(send_packet) => Some(send_packet.packet.data),
+                _ => None,
+            })
+            .expect("send_transfer must emit a SendPacket event");
+        let data: PacketData = serde_json::from_slice(&packet_data).unwrap();
+        assert_eq!(data.memo, msg.memo);
+    }
+
+    #[test]
+    fn test_burn_vouchers() {
+        let voucher = PrefixedCoin {
+            denom: "transfer/channel-0/uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let msg = get_dummy_msg_transfer_of(voucher.clone());
+        let sender = msg.sender;
+        let mut ctx = get_dummy_transfer_module().with_balance(sender.clone(), voucher.clone());
+        let burn = |ctx: &mut DummyTransferModule, voucher: &PrefixedCoin| {
+            burn_vouchers(ctx, &sender, voucher, &msg.source_port, msg.source_channel)
+        };
+
+        let over = PrefixedCoin {
+            amount: 11u64.into(),
+            ..voucher.clone()
+        };
+        let err = burn(&mut ctx, &over).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::InsufficientFunds(_)));
+        assert_eq!(ctx.balance(&sender, &voucher.denom), voucher.amount);
+
+        burn(&mut ctx, &voucher).unwrap();
+        assert_eq!(ctx.balance(&sender, &voucher.denom), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_burn_vouchers_mismatched_source_channel() {
+        // a voucher received over transfer/channel-1 must not be burnt when sent over channel-0
+        let voucher = PrefixedCoin {
+            denom: "transfer/channel-1/uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let msg = get_dummy_msg_transfer_of(voucher.clone());
+        let mut ctx = get_dummy_transfer_module().with_balance(msg.sender.clone(), voucher.clone());
+
+        let err = burn_vouchers(
+            &mut ctx,
+            &msg.sender,
+            &voucher,
+            &msg.source_port,
+            msg.source_channel,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::MismatchedSourceChannel(_)
+        ));
+        assert_eq!(ctx.balance(&msg.sender, &voucher.denom), voucher.amount);
+    }
+
+    #[test]
+    fn test_send_transfers() {
+        let coins: Vec<PrefixedCoin> = vec![
+            PrefixedCoin {
+                denom: "uatom".parse().unwrap(),
+                amount: 10u64.into(),
+            },
+            PrefixedCoin {
+                denom: "transfer/channel-1/uosmo".parse().unwrap(),
+                amount: 20u64.into(),
+            },
+        ];
+        let msg_of = |token: Vec<PrefixedCoin>| {
+            let msg = get_dummy_msg_transfer_of(coins[0].clone());
+            MsgTransfer {
+                source_port: msg.source_port,
+                source_channel: msg.source_channel,
+                token,
+                sender: msg.sender,
+                receiver: msg.receiver,
+                timeout_height: msg.timeout_height,
+                timeout_timestamp: msg.timeout_timestamp,
+                memo: msg.memo,
+            }
+        };
+
+        let sender = msg_of(vec![]).sender;
+        let mut ctx = coins.iter().fold(get_dummy_transfer_module(), |ctx, coin| {
+            ctx.with_balance(sender.clone(), coin.clone())
+        });
+
+        let err =
+            send_transfers(&mut ctx, &mut HandlerOutputBuilder::new(), msg_of(vec![])).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::EmptyTransfer(_)));
+
+        let zero = PrefixedCoin {
+            amount: 0u64.into(),
+            ..coins[0].clone()
+        };
+        let err = send_transfers(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            msg_of(vec![coins[0].clone(), zero]),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::ZeroAmount(_)));
+
+        let sequences = send_transfers(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            msg_of(coins.clone()),
+        )
+        .unwrap();
+        assert_eq!(sequences, vec![Sequence::from(1), Sequence::from(2)]);
+        for coin in &coins {
+            assert_eq!(ctx.balance(&sender, &coin.denom), Amount::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_send_transfer_hashed_base_denom() {
+        let token = PrefixedCoin {
+            denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+                .parse::<BaseDenom>()
+                .unwrap()
+                .into(),
+            amount: 10u64.into(),
+        };
+        let sender = get_dummy_msg_transfer_of(token.clone()).sender;
+
+        let mut ctx = get_dummy_transfer_module()
+            .with_strict_denom_check()
+            .with_balance(sender.clone(), token.clone());
+        let err = send_transfer(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            get_dummy_msg_transfer_of(token.clone()),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::HashedBaseDenom(_)));
+
+        let mut ctx = get_dummy_transfer_module().with_balance(sender, token.clone());
+        let mut output = HandlerOutputBuilder::new();
+        send_transfer(&mut ctx, &mut output, get_dummy_msg_transfer_of(token)).unwrap();
+        assert!(output
+            .with_result(())
+            .log
+            .iter()
+            .any(|line| line.starts_with("warning")));
+    }
+
+    #[test]
+    fn test_send_transfer_zero_amount() {
+        let token = |amount: u64| PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: amount.into(),
+        };
+        let sender = get_dummy_msg_transfer_of(token(1)).sender;
+        let mut ctx = get_dummy_transfer_module().with_balance(sender, token(1));
+
+        let err = send_transfer(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            get_dummy_msg_transfer_of(token(0)),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::ZeroAmount(_)));
+
+        let res = send_transfer(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            get_dummy_msg_transfer_of(token(1)),
+        );
+        assert!(res.is_ok(), "{:?}", res);
+    }
+
+    #[test]
+    fn test_send_transfer_returns_packet_commitment() {
+        let token = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let msg = get_dummy_msg_transfer_of(token.clone());
+        let mut ctx = get_dummy_transfer_module().with_balance(msg.sender.clone(), token);
+
+        let mut output = HandlerOutputBuilder::new();
+        let commitment = send_transfer(&mut ctx, &mut output, msg).unwrap();
+
+        let packet = output
+            .with_result(())
+            .events
+            .into_iter()
+            .find_map(|event| match event {
+                IbcEvent::SendPacket(send_packet) => Some(send_packet.packet),
+                _ => None,
+            })
+            .expect("send_transfer must emit a SendPacket event");
+        assert_eq!(
+            commitment,
+            ctx.packet_commitment(
+                packet.data.clone(),
+                packet.timeout_height,
+                packet.timeout_timestamp
+            )
+        );
+        assert_eq!(
+            ctx.get_packet_commitment(&(
+                packet.source_port,
+                packet.source_channel,
+                packet.sequence
+            ))
+            .unwrap(),
+            commitment
+        );
+    }
+
+    #[test]
+    fn test_send_transfer_forbidden_recipient() {
+        let token = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let blackhole: Signer = "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a"
+            .parse()
+            .unwrap();
+        let msg = MsgTransfer {
+            receiver: blackhole.clone(),
+            ..get_dummy_msg_transfer_of(token.clone())
+        };
+        let mut ctx = get_dummy_transfer_module()
+            .with_forbidden_recipient(blackhole)
+            .with_balance(msg.sender.clone(), token.clone());
+
+        let err =
+            send_transfer(&mut ctx, &mut HandlerOutputBuilder::new(), msg.clone()).unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::ForbiddenRecipient(_)));
+        assert_eq!(ctx.balance(&msg.sender, &token.denom), token.amount);
+    }
+
+    #[test]
+    fn test_send_transfer_with_fee() {
+        let token = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let fee_collector: Signer = "cosmos1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqnrql8a"
+            .parse()
+            .unwrap();
+        let msg = get_dummy_msg_transfer_of(token.clone());
+        let sent_packet_data = |output: HandlerOutputBuilder<()>| -> PacketData {
+            let data = output
+                .with_result(())
+                .events
+                .into_iter()
+                .find_map(|event| match event {
+                    IbcEvent::SendPacket(send_packet) => Some(send_packet.packet.data),
+                    _ => None,
+                })
+                .expect("send_transfer must emit a SendPacket event");
+            serde_json::from_slice(&data).unwrap()
+        };
+
+        // a fee smaller than the amount is collected and the packet carries the remainder
+        let mut ctx = get_dummy_transfer_module().with_balance(msg.sender.clone(), token.clone());
+        let mut output = HandlerOutputBuilder::new();
+        send_transfer_with_fee(
+            &mut ctx,
+            &mut output,
+            msg.clone(),
+            Some((fee_collector.clone(), 3u64.into())),
+        )
+        .unwrap();
+        assert_eq!(sent_packet_data(output).token.amount, Amount::from(7u64));
+        assert_eq!(ctx.balance(&msg.sender, &token.denom), Amount::ZERO);
+        assert_eq!(
+            ctx.balance(&fee_collector, &token.denom),
+            Amount::from(3u64)
+        );
+
+        // a fee equal to or greater than the amount is rejected without touching any balance
+        for fee in [10u64, 11] {
+            let mut ctx =
+                get_dummy_transfer_module().with_balance(msg.sender.clone(), token.clone());
+            let err = send_transfer_with_fee(
+                &mut ctx,
+                &mut HandlerOutputBuilder::new(),
+                msg.clone(),
+                Some((fee_collector.clone(), fee.into())),
+            )
+            .unwrap_err();
+            assert!(matches!(err.detail(), ErrorDetail::InvalidFee(_)));
+            assert_eq!(ctx.balance(&msg.sender, &token.denom), token.amount);
+            assert_eq!(ctx.balance(&fee_collector, &token.denom), Amount::ZERO);
+        }
+
+        // without a fee, the full amount is transferred
+        let mut ctx = get_dummy_transfer_module().with_balance(msg.sender.clone(), token.clone());
+        let mut output = HandlerOutputBuilder::new();
+        send_transfer_with_fee(&mut ctx, &mut output, msg.clone(), None).unwrap();
+        assert_eq!(sent_packet_data(output).token.amount, token.amount);
+        assert_eq!(ctx.balance(&msg.sender, &token.denom), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_send_transfer_disabled_denom() {
+        let coin = |denom: &str| PrefixedCoin {
+            denom: denom.parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let sender = get_dummy_msg_transfer_of(coin("uatom")).sender;
+        let mut ctx = get_dummy_transfer_module()
+            .with_disabled_denom("uatom".parse().unwrap())
+            .with_balance(sender.clone(), coin("uatom"))
+            .with_balance(sender, coin("uosmo"));
+
+        let err = send_transfer(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            get_dummy_msg_transfer_of(coin("uatom")),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::SendDisabled(_)));
+
+        let res = send_transfer(
+            &mut ctx,
+            &mut HandlerOutputBuilder::new(),
+            get_dummy_msg_transfer_of(coin("uosmo")),
+        );
+        assert!(res.is_ok(), "{:?}", res);
+    }
+
+    #[test]
+    fn test_send_transfer_timeout_validation() {
+        let token = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let msg_with_timeout = |revision_height: u64, timeout_timestamp: Timestamp| MsgTransfer {
+            timeout_height: Height::new(0, revision_height),
+            timeout_timestamp,
+            ..get_dummy_msg_transfer_of(token.clone())
+        };
+        let sender = msg_with_timeout(0, Timestamp::none()).sender;
+        let ctx = || {
+            get_dummy_transfer_module()
+                .with_host_height(Height::new(0, 5))
+                .with_balance(sender.clone(), token.clone())
+        };
+
+        // both the timeout height and timestamp are unset
+        let err = send_transfer(
+            &mut ctx(),
+            &mut HandlerOutputBuilder::new(),
+            msg_with_timeout(0, Timestamp::none()),
+        )
+        .unwrap_err();
+        assert!(matches!(err.detail(), ErrorDetail::InvalidTimeout(_)));
+
+        // the timeout height is in the future
+        let res = send_transfer(
+            &mut ctx(),
+            &mut HandlerOutputBuilder::new(),
+            msg_with_timeout(10, Timestamp::none()),
+        );
+        assert!(res.is_ok(), "{:?}", res);
+
+        // the timeout height has already been reached
+        for revision_height in [4, 5] {
+            let err = send_transfer(
+                &mut ctx(),
+                &mut HandlerOutputBuilder::new(),
+                msg_with_timeout(revision_height, Timestamp::none()),
+            )
+            .unwrap_err();
+            assert!(matches!(err.detail(), ErrorDetail::InvalidTimeout(_)));
+        }
+    }
+
+    #[test]
+    fn test_send_transfer_timeout_timestamp_validation() {
+        let token = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let host_height = Height::new(0, 5);
+        let host_timestamp = Timestamp::from_nanoseconds(1_000_000_000).unwrap();
+        let msg_with_timeout = |timeout_timestamp: Timestamp| MsgTransfer {
+            timeout_height: Height::zero(),
+            timeout_timestamp,
+            ..get_dummy_msg_transfer_of(token.clone())
+        };
+        let sender = msg_with_timeout(Timestamp::none()).sender;
+        let ctx = || {
+            let consensus_state = MockConsensusState::new(
+                MockHeader::new(host_height).with_timestamp(host_timestamp),
+            );
+            get_dummy_transfer_module()
+                .with_host_height(host_height)
+                .with_host_consensus_state(host_height, consensus_state.into())
+                .with_balance(sender.clone(), token.clone())
+        };
+        let at = |nanoseconds: u64| Timestamp::from_nanoseconds(nanoseconds).unwrap();
+
+        // the timeout timestamp is in the future
+        let res = send_transfer(
+            &mut ctx(),
+            &mut HandlerOutputBuilder::new(),
+            msg_with_timeout(at(1_000_000_001)),
+        );
+        assert!(res.is_ok(), "{:?}", res);
+
+        // the timeout timestamp has already been reached
+        for nanoseconds in [999_999_999, 1_000_000_000] {
+            let err = send_transfer(
+                &mut ctx(),
+                &mut HandlerOutputBuilder::new(),
+                msg_with_timeout(at(nanoseconds)),
+            )
+            .unwrap_err();
+            assert!(matches!(err.detail(), ErrorDetail::InvalidTimeout(_)));
+        }
+    }
+
+    #[test]
+    fn test_send_transfer_memo_too_large() {
+        let token = PrefixedCoin {
+            denom: "uatom".parse().unwrap(),
+            amount: 10u64.into(),
+        };
+        let msg = MsgTransfer {
+            memo: "a".repeat(40_000).into(),
+            ..get_dummy_msg_transfer_of(token.clone())
+        };
+        let mut ctx = get_dummy_transfer_module().with_balance(msg.sender.clone(), token);
+
+        let err = send_transfer(&mut ctx, &mut HandlerOutputBuilder::new(), msg).unwrap_err();
+        assert!(matches!(
+            err.detail(),
+            ErrorDetail::PacketDataTooLarge(e) if e.field == "memo"
+        ));
+    }
 }
@@ -1,8 +1,157 @@
 #![allow(clippy::assign_op_pattern)]
 #![allow(clippy::ptr_offset_with_cast)]
 
+use flex_error::define_error;
 use uint::construct_uint;
 
+use crate::prelude::*;
+
 construct_uint! {
     pub struct U256(4);
 }
+
+define_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    Error {
+        InvalidDecimal
+            { value: String, reason: String }
+            | e | { format_args!("invalid decimal {0}: {1}", e.value, e.reason) },
+
+        PrecisionExceeded
+            { value: String, decimals: u8 }
+            | e | { format_args!("decimal {0} has more than {1} fractional digits", e.value, e.decimals) },
+    }
+}
+
+impl U256 {
+    /// Raises `self` to the power of `exp`, returning `None` on overflow.
+    /// Named apart from the `checked_pow(self, U256)` generated by `construct_uint!`, for the
+    /// common case of small exponents such as decimal scales.
+    pub fn checked_pow_u32(self, exp: u32) -> Option<U256> {
+        let mut result = U256::one();
+        let mut base = self;
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.checked_mul(base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.checked_mul(base)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Parses a decimal string (e.g. "1.5") and scales it by `10^decimals`, e.g. "1.5" with 6
+    /// decimals is parsed as 1_500_000. Fails if the string has more fractional digits than
+    /// `decimals` or if the scaled value overflows.
+    pub fn from_decimal_str(s: &str, decimals: u8) -> Result<U256, Error> {
+        let invalid = |reason: &str| Error::invalid_decimal(s.to_string(), reason.to_string());
+
+        let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if integer.is_empty() || (s.contains('.') && fraction.is_empty()) {
+            return Err(invalid("missing digits"));
+        }
+        if !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid("only ASCII digits and a single '.' are allowed"));
+        }
+        if fraction.len() > usize::from(decimals) {
+            return Err(Error::precision_exceeded(s.to_string(), decimals));
+        }
+
+        // scales `digits` by `10^exp`, where zero stays zero however large the scale
+        let scaled = |digits: &str, exp: u8| {
+            if digits.bytes().all(|b| b == b'0') {
+                return Ok(U256::zero());
+            }
+            U256::from_dec_str(digits)
+                .ok()
+                .zip(U256::from(10).checked_pow_u32(u32::from(exp)))
+                .and_then(|(value, scale)| value.checked_mul(scale))
+                .ok_or_else(|| invalid("overflow"))
+        };
+
+        let fraction_exp = decimals - fraction.len() as u8;
+        scaled(integer, decimals)?
+            .checked_add(scaled(fraction, fraction_exp)?)
+            .ok_or_else(|| invalid("overflow"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_pow_u32() {
+        assert_eq!(
+            U256::from(10).checked_pow_u32(6),
+            Some(U256::from(1_000_000))
+        );
+        assert_eq!(U256::from(7).checked_pow_u32(0), Some(U256::one()));
+        assert_eq!(U256::zero().checked_pow_u32(0), Some(U256::one()));
+        assert_eq!(U256::zero().checked_pow_u32(5), Some(U256::zero()));
+        assert!(U256::from(2).checked_pow_u32(255).is_some());
+        assert_eq!(
+            U256::from(10).checked_pow_u32(77),
+            U256::from_dec_str(&format!("1{}", "0".repeat(77))).ok()
+        );
+
+        // overflow
+        assert_eq!(U256::from(2).checked_pow_u32(256), None);
+        assert_eq!(U256::from(10).checked_pow_u32(78), None);
+        assert_eq!(U256::MAX.checked_pow_u32(2), None);
+        assert_eq!(U256::MAX.checked_pow_u32(1), Some(U256::MAX));
+    }
+
+    #[test]
+    fn test_from_decimal_str() {
+        assert_eq!(
+            U256::from_decimal_str("1.5", 6).unwrap(),
+            U256::from(1_500_000)
+        );
+        assert_eq!(
+            U256::from_decimal_str("001.050", 6).unwrap(),
+            U256::from(1_050_000)
+        );
+        assert_eq!(U256::from_decimal_str("42", 0).unwrap(), U256::from(42));
+        assert_eq!(
+            U256::from_decimal_str("0.000001", 6).unwrap(),
+            U256::from(1)
+        );
+
+        assert!(matches!(
+            U256::from_decimal_str("1.0000001", 6).unwrap_err().detail(),
+            ErrorDetail::PrecisionExceeded(_)
+        ));
+        for invalid in ["", ".5", "1.", "1.2.3", "-1", "1e6", " 1"] {
+            assert!(matches!(
+                U256::from_decimal_str(invalid, 6).unwrap_err().detail(),
+                ErrorDetail::InvalidDecimal(_)
+            ));
+        }
+
+        // 10^78 does not fit, but zero scales to zero
+        assert_eq!(U256::from_decimal_str("0.0", 78).unwrap(), U256::zero());
+        assert!(matches!(
+            U256::from_decimal_str("1", 78).unwrap_err().detail(),
+            ErrorDetail::InvalidDecimal(_)
+        ));
+        assert_eq!(
+            U256::from_decimal_str("0.5", 77).unwrap(),
+            U256::from(5) * U256::from(10).checked_pow_u32(76).unwrap()
+        );
+
+        let max = U256::MAX.to_string();
+        assert!(U256::from_decimal_str(&max, 0).is_ok());
+        assert!(matches!(
+            U256::from_decimal_str(&max, 1).unwrap_err().detail(),
+            ErrorDetail::InvalidDecimal(_)
+        ));
+    }
+}
@@ -27,6 +27,12 @@ impl Version {
         Self::new(transfer::VERSION.to_string())
     }
 
+    /// Returns true if this is a version of the ICS20 application recognized by
+    /// [`Ics20Version`](transfer::Ics20Version), e.g. `ics20-1`.
+    pub fn is_ics20(&self) -> bool {
+        self.0.parse::<transfer::Ics20Version>().is_ok()
+    }
+
     pub fn empty() -> Self {
         Self::new("".to_string())
     }
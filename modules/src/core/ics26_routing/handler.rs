@@ -227,7 +227,16 @@ mod tests {
         // We reuse this same context across all tests. Nothing in particular needs parametrizing.
         let mut ctx = {
             let ctx = MockContext::default();
-            let module = DummyTransferModule::new(ctx.ibc_store_share());
+            // Fund the sender of the transfer messages below.
+            let module = {
+                let msg_transfer = get_dummy_msg_transfer(0);
+                let funds = PrefixedCoin {
+                    denom: msg_transfer.token.denom,
+                    amount: 100u64.into(),
+                };
+                DummyTransferModule::new(ctx.ibc_store_share())
+                    .with_balance(msg_transfer.sender, funds)
+            };
             let router = MockRouterBuilder::default()
                 .add_route(transfer_module_id.clone(), module)
                 .unwrap()
@@ -309,6 +318,7 @@ mod tests {
                 },
                 sender: msg_transfer_two.sender.clone(),
                 receiver: msg_transfer_two.receiver.clone(),
+                memo: msg_transfer_two.memo.clone(),
             };
             serde_json::to_vec(&data).expect("PacketData's infallible Serialize impl failed")
         };
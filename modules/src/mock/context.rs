@@ -361,7 +361,7 @@ impl MockContext {
         chan_id: ChannelId,
         seq_number: Sequence,
     ) -> Self {
-        let mut next_sequence_ack = self.ibc_store.lock().unwrap().next_sequence_send.clone();
+        let mut next_sequence_ack = self.ibc_store.lock().unwrap().next_sequence_ack.clone();
         next_sequence_ack.insert((port_id, chan_id), seq_number);
         self.ibc_store.lock().unwrap().next_sequence_ack = next_sequence_ack;
         self
@@ -592,6 +592,14 @@ pub struct MockIbcStore {
 
     // Used by unordered channel
     pub packet_receipt: BTreeMap<(PortId, ChannelId, Sequence), Receipt>,
+
+    /// The host chain height seen by modules sharing this store (e.g. `DummyTransferModule`).
+    /// `MockContext` itself derives its height from its history of host blocks instead.
+    pub host_height: Height,
+
+    /// The consensus states of the host chain seen by modules sharing this store, indexed by
+    /// height.
+    pub host_consensus_states: BTreeMap<Height, AnyConsensusState>,
 }
 
 #[derive(Default)]
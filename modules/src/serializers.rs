@@ -36,3 +36,180 @@ pub mod serde_string {
             .map_err(de::Error::custom)
     }
 }
+
+/// Serializes a value as a JSON number if it fits in a `u64`, and as a string otherwise (e.g. for
+/// `Amount`s). Deserializes from either form.
+pub mod serde_amount_number {
+    use alloc::string::{String, ToString};
+    use core::fmt::{self, Display};
+    use core::marker::PhantomData;
+    use core::str::FromStr;
+
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        let value = value.to_string();
+        match value.parse::<u64>() {
+            Ok(number) => serializer.serialize_u64(number),
+            Err(_) => serializer.serialize_str(&value),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(NumberOrStringVisitor(PhantomData))
+    }
+
+    struct NumberOrStringVisitor<T>(PhantomData<T>);
+
+    impl<'de, T> de::Visitor<'de> for NumberOrStringVisitor<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("an unsigned integer or a string")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+            self.visit_str(&v.to_string())
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            v.parse().map_err(E::custom)
+        }
+
+        fn visit_string<E: de::Error>(self, v: String) -> Result<T, E> {
+            self.visit_str(&v)
+        }
+    }
+}
+
+/// Serializes a `BaseCoin` as the Cosmos SDK does on some interfaces, i.e. as a single string of
+/// the amount immediately followed by the denomination (e.g. "100uatom"). Deserializes from that
+/// form, splitting it where the leading digits end, as base denominations start with a letter.
+pub mod serde_sdk_coin {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::applications::transfer::error::Error;
+    use crate::applications::transfer::BaseCoin;
+    use crate::prelude::*;
+
+    pub fn serialize<S>(coin: &BaseCoin, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&format_args!("{}{}", coin.amount, coin.denom))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<BaseCoin, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        parse(&String::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+
+    fn parse(s: &str) -> Result<BaseCoin, Error> {
+        let boundary = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (amount, denom) = s.split_at(boundary);
+        if amount.is_empty() {
+            return Err(Error::invalid_coin_amount(
+                amount.to_string(),
+                "missing amount".to_string(),
+            ));
+        }
+
+        Ok(BaseCoin {
+            denom: denom
+                .parse()
+                .map_err(|e: Error| Error::invalid_coin_denom(denom.to_string(), e.to_string()))?,
+            amount: amount.parse().map_err(|e: Error| {
+                Error::invalid_coin_amount(amount.to_string(), e.to_string())
+            })?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::{serde_amount_number, serde_sdk_coin};
+    use crate::applications::transfer::{Amount, BaseCoin};
+    use crate::bigint::U256;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Wrapper {
+        #[serde(with = "serde_amount_number")]
+        amount: Amount,
+    }
+
+    #[test]
+    fn test_serde_amount_number_round_trip() {
+        let small = Wrapper {
+            amount: Amount::from(42u64),
+        };
+        let json = serde_json::to_string(&small).unwrap();
+        assert_eq!(json, r#"{"amount":42}"#);
+        assert_eq!(serde_json::from_str::<Wrapper>(&json).unwrap(), small);
+
+        let large = Wrapper {
+            amount: (U256::from(u64::MAX) + U256::one()).into(),
+        };
+        let json = serde_json::to_string(&large).unwrap();
+        assert_eq!(json, r#"{"amount":"18446744073709551616"}"#);
+        assert_eq!(serde_json::from_str::<Wrapper>(&json).unwrap(), large);
+
+        // the string form of small amounts is accepted too
+        let json = r#"{"amount":"42"}"#;
+        assert_eq!(serde_json::from_str::<Wrapper>(json).unwrap(), small);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct CoinWrapper {
+        #[serde(with = "serde_sdk_coin")]
+        coin: BaseCoin,
+    }
+
+    #[test]
+    fn test_serde_sdk_coin_round_trip() {
+        for (json, denom, amount) in [
+            (r#"{"coin":"100uatom"}"#, "uatom", 100u64),
+            (
+                r#"{"coin":"5ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"}"#,
+                "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+                5,
+            ),
+            // the denomination may contain digits after its leading letter
+            (r#"{"coin":"0a1b2c"}"#, "a1b2c", 0),
+        ] {
+            let wrapper = CoinWrapper {
+                coin: BaseCoin {
+                    denom: denom.parse().unwrap(),
+                    amount: amount.into(),
+                },
+            };
+            assert_eq!(serde_json::to_string(&wrapper).unwrap(), json);
+            assert_eq!(serde_json::from_str::<CoinWrapper>(json).unwrap(), wrapper);
+        }
+
+        for invalid in ["uatom", "100", "", "100 uatom", "-100uatom"] {
+            let json = format!(r#"{{"coin":"{}"}}"#, invalid);
+            assert!(
+                serde_json::from_str::<CoinWrapper>(&json).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+}
@@ -1,10 +1,15 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use tendermint::{block, consensus, evidence, public_key::Algorithm};
 
 use crate::applications::transfer::context::{BankKeeper, Ics20Context, Ics20Keeper, Ics20Reader};
-use crate::applications::transfer::{error::Error as Ics20Error, PrefixedCoin};
+use crate::applications::transfer::packet::ForwardMemo;
+use crate::applications::transfer::{
+    error::Error as Ics20Error, Amount, DenomTrace, HashedDenom, PrefixedCoin, PrefixedDenom,
+    TracePrefix, VERSION,
+};
 use crate::core::ics02_client::client_consensus::AnyConsensusState;
 use crate::core::ics02_client::client_state::AnyClientState;
 use crate::core::ics02_client::error::Error as Ics02Error;
@@ -63,11 +68,162 @@ pub fn get_dummy_bech32_account() -> String {
 #[derive(Debug)]
 pub struct DummyTransferModule {
     ibc_store: Arc<Mutex<MockIbcStore>>,
+    /// The bank balances, indexed by account and denomination.
+    balances: BTreeMap<(Signer, PrefixedDenom), Amount>,
+    /// Whether counterparty chains only support amounts that fit in a `u128`.
+    u128_bounded_counterparty: bool,
+    /// Translations of foreign receiver addresses to local signers.
+    receiver_translations: BTreeMap<String, Signer>,
+    /// Whether tokens with a hashed base denomination are rejected at send time.
+    strict_denom_check: bool,
+    /// The denomination traces, indexed by their hash.
+    denom_traces: BTreeMap<HashedDenom, DenomTrace>,
+    /// The trusted trace prefixes if in strict receive mode.
+    known_trace_prefixes: Option<BTreeSet<TracePrefix>>,
+    /// The decimals lost when representing received amounts locally, if such amounts are rejected.
+    excess_recv_decimals: Option<u8>,
+    /// The addresses tokens must not be sent to.
+    forbidden_recipients: BTreeSet<Signer>,
+    /// The forwarding instructions executed so far, along with the forwarded coins.
+    forwarded: Vec<(ForwardMemo, PrefixedCoin)>,
+    /// The denominations that must not be received.
+    blocked_denoms: BTreeSet<PrefixedDenom>,
+    /// The denominations for which send and receive are disabled.
+    disabled_denoms: BTreeSet<PrefixedDenom>,
+    /// The caps on the total supply of voucher denominations.
+    voucher_supply_caps: BTreeMap<PrefixedDenom, Amount>,
+    /// The ICS20 version of transfer channels.
+    version: String,
 }
 
 impl DummyTransferModule {
     pub fn new(ibc_store: Arc<Mutex<MockIbcStore>>) -> Self {
-        Self { ibc_store }
+        Self {
+            ibc_store,
+            balances: BTreeMap::new(),
+            u128_bounded_counterparty: false,
+            receiver_translations: BTreeMap::new(),
+            strict_denom_check: false,
+            denom_traces: BTreeMap::new(),
+            known_trace_prefixes: None,
+            excess_recv_decimals: None,
+            forbidden_recipients: BTreeSet::new(),
+            forwarded: Vec::new(),
+            blocked_denoms: BTreeSet::new(),
+            disabled_denoms: BTreeSet::new(),
+            voucher_supply_caps: BTreeMap::new(),
+            version: VERSION.to_string(),
+        }
+    }
+
+    /// Marks all counterparty chains as only supporting amounts that fit in a `u128`.
+    pub fn with_u128_bounded_counterparty(self) -> Self {
+        Self {
+            u128_bounded_counterparty: true,
+            ..self
+        }
+    }
+
+    /// Rejects sending tokens whose base denomination is a hashed IBC denomination.
+    pub fn with_strict_denom_check(self) -> Self {
+        Self {
+            strict_denom_check: true,
+            ..self
+        }
+    }
+
+    /// Only mints vouchers whose trace consists of the `known` prefixes.
+    pub fn with_known_trace_prefixes(self, known: BTreeSet<TracePrefix>) -> Self {
+        Self {
+            known_trace_prefixes: Some(known),
+            ..self
+        }
+    }
+
+    /// Rejects received amounts that are not a multiple of `10^excess_decimals`.
+    pub fn with_excess_recv_decimals(self, excess_decimals: u8) -> Self {
+        Self {
+            excess_recv_decimals: Some(excess_decimals),
+            ..self
+        }
+    }
+
+    /// Rejects sending tokens to `receiver`.
+    pub fn with_forbidden_recipient(mut self, receiver: Signer) -> Self {
+        self.forbidden_recipients.insert(receiver);
+        self
+    }
+
+    /// Rejects receiving tokens of the given denomination.
+    pub fn with_blocked_denom(mut self, denom: PrefixedDenom) -> Self {
+        self.blocked_denoms.insert(denom);
+        self
+    }
+
+    /// Sets the height of the host chain.
+    pub fn with_host_height(self, height: Height) -> Self {
+        self.ibc_store.lock().unwrap().host_height = height;
+        self
+    }
+
+    /// Sets the consensus state of the host chain at the given height.
+    pub fn with_host_consensus_state(
+        self,
+        height: Height,
+        consensus_state: AnyConsensusState,
+    ) -> Self {
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .host_consensus_states
+            .insert(height, consensus_state);
+        self
+    }
+
+    /// Disables sending and receiving tokens of the given denomination.
+    pub fn with_disabled_denom(mut self, denom: PrefixedDenom) -> Self {
+        self.disabled_denoms.insert(denom);
+        self
+    }
+
+    /// Caps the total supply of the given voucher denomination.
+    pub fn with_voucher_supply_cap(mut self, denom: PrefixedDenom, cap: Amount) -> Self {
+        self.voucher_supply_caps.insert(denom, cap);
+        self
+    }
+
+    /// Negotiates the given custom ICS20 version on transfer channels.
+    pub fn with_version(self, version: &str) -> Self {
+        Self {
+            version: version.to_string(),
+            ..self
+        }
+    }
+
+    /// Credits tokens received for the foreign address `raw` to `receiver`.
+    pub fn with_receiver_translation(mut self, raw: &str, receiver: Signer) -> Self {
+        self.receiver_translations.insert(raw.to_string(), receiver);
+        self
+    }
+
+    /// Credits the given coin to the balance of `account`.
+    pub fn with_balance(mut self, account: Signer, coin: PrefixedCoin) -> Self {
+        self.mint_coins(&account, &coin)
+            .expect("balance overflow in DummyTransferModule");
+        self
+    }
+
+    /// Returns the balance of `account` for the given denomination.
+    pub fn balance(&self, account: &Signer, denom: &PrefixedDenom) -> Amount {
+        self.balances
+            .get(&(account.clone(), denom.clone()))
+            .copied()
+            .unwrap_or(Amount::ZERO)
+    }
+
+    /// Returns the forwarding instructions executed so far, along with the forwarded coins.
+    pub fn forwarded(&self) -> &[(ForwardMemo, PrefixedCoin)] {
+        &self.forwarded
     }
 }
 
@@ -89,6 +245,33 @@ impl Module for DummyTransferModule {
 
 impl Ics20Keeper for DummyTransferModule {
     type AccountId = Signer;
+
+    fn set_denom_trace(&mut self, denom_trace: &DenomTrace) -> Result<(), Ics20Error> {
+        self.denom_traces
+            .insert(denom_trace.hashed(), denom_trace.clone());
+        Ok(())
+    }
+
+    fn export_denom_traces(&self) -> Vec<(HashedDenom, DenomTrace)> {
+        self.denom_traces
+            .iter()
+            .map(|(hash, trace)| (*hash, trace.clone()))
+            .collect()
+    }
+
+    fn import_denom_traces(
+        &mut self,
+        entries: Vec<(HashedDenom, DenomTrace)>,
+    ) -> Result<(), Ics20Error> {
+        if let Some((hash, trace)) = entries.iter().find(|(hash, trace)| *hash != trace.hashed()) {
+            return Err(Ics20Error::mismatched_denom_trace_hash(
+                *hash,
+                trace.clone(),
+            ));
+        }
+        self.denom_traces.extend(entries);
+        Ok(())
+    }
 }
 
 impl ChannelKeeper for DummyTransferModule {
@@ -107,32 +290,52 @@ impl ChannelKeeper for DummyTransferModule {
 
     fn delete_packet_commitment(
         &mut self,
-        _key: (PortId, ChannelId, Sequence),
+        key: (PortId, ChannelId, Sequence),
     ) -> Result<(), Error> {
-        unimplemented!()
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .packet_commitment
+            .remove(&key);
+        Ok(())
     }
 
     fn store_packet_receipt(
         &mut self,
-        _key: (PortId, ChannelId, Sequence),
-        _receipt: Receipt,
+        key: (PortId, ChannelId, Sequence),
+        receipt: Receipt,
     ) -> Result<(), Error> {
-        unimplemented!()
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .packet_receipt
+            .insert(key, receipt);
+        Ok(())
     }
 
     fn store_packet_acknowledgement(
         &mut self,
-        _key: (PortId, ChannelId, Sequence),
-        _ack: AcknowledgementCommitment,
+        key: (PortId, ChannelId, Sequence),
+        ack: AcknowledgementCommitment,
     ) -> Result<(), Error> {
-        unimplemented!()
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .packet_acknowledgement
+            .insert(key, ack);
+        Ok(())
     }
 
     fn delete_packet_acknowledgement(
         &mut self,
-        _key: (PortId, ChannelId, Sequence),
+        key: (PortId, ChannelId, Sequence),
     ) -> Result<(), Error> {
-        unimplemented!()
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .packet_acknowledgement
+            .remove(&key);
+        Ok(())
     }
 
     fn store_connection_channels(
@@ -145,10 +348,15 @@ impl ChannelKeeper for DummyTransferModule {
 
     fn store_channel(
         &mut self,
-        _port_channel_id: (PortId, ChannelId),
-        _channel_end: &ChannelEnd,
+        port_channel_id: (PortId, ChannelId),
+        channel_end: &ChannelEnd,
     ) -> Result<(), Error> {
-        unimplemented!()
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .channels
+            .insert(port_channel_id, channel_end.clone());
+        Ok(())
     }
 
     fn store_next_sequence_send(
@@ -166,18 +374,28 @@ impl ChannelKeeper for DummyTransferModule {
 
     fn store_next_sequence_recv(
         &mut self,
-        _port_channel_id: (PortId, ChannelId),
-        _seq: Sequence,
+        port_channel_id: (PortId, ChannelId),
+        seq: Sequence,
     ) -> Result<(), Error> {
-        unimplemented!()
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_recv
+            .insert(port_channel_id, seq);
+        Ok(())
     }
 
     fn store_next_sequence_ack(
         &mut self,
-        _port_channel_id: (PortId, ChannelId),
-        _seq: Sequence,
+        port_channel_id: (PortId, ChannelId),
+        seq: Sequence,
     ) -> Result<(), Error> {
-        unimplemented!()
+        self.ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_ack
+            .insert(port_channel_id, seq);
+        Ok(())
     }
 
     fn increase_channel_counter(&mut self) {
@@ -186,8 +404,11 @@ impl ChannelKeeper for DummyTransferModule {
 }
 
 impl PortReader for DummyTransferModule {
-    fn lookup_module_by_port(&self, _port_id: &PortId) -> Result<ModuleId, PortError> {
-        unimplemented!()
+    fn lookup_module_by_port(&self, port_id: &PortId) -> Result<ModuleId, PortError> {
+        match self.ibc_store.lock().unwrap().port_to_module.get(port_id) {
+            Some(module_id) => Ok(module_id.clone()),
+            None => Err(PortError::unknown_port(port_id.clone())),
+        }
     }
 }
 
@@ -196,26 +417,39 @@ impl BankKeeper for DummyTransferModule {
 
     fn send_coins(
         &mut self,
-        _from: &Self::AccountId,
-        _to: &Self::AccountId,
-        _amt: &PrefixedCoin,
+        from: &Self::AccountId,
+        to: &Self::AccountId,
+        amt: &PrefixedCoin,
     ) -> Result<(), Ics20Error> {
-        Ok(())
+        self.burn_coins(from, amt)?;
+        self.mint_coins(to, amt)
     }
 
     fn mint_coins(
         &mut self,
-        _account: &Self::AccountId,
-        _amt: &PrefixedCoin,
+        account: &Self::AccountId,
+        amt: &PrefixedCoin,
     ) -> Result<(), Ics20Error> {
+        let balance = self
+            .balance(account, &amt.denom)
+            .checked_add(amt.amount)
+            .ok_or_else(Ics20Error::invalid_token)?;
+        self.balances
+            .insert((account.clone(), amt.denom.clone()), balance);
         Ok(())
     }
 
     fn burn_coins(
         &mut self,
-        _account: &Self::AccountId,
-        _amt: &PrefixedCoin,
+        account: &Self::AccountId,
+        amt: &PrefixedCoin,
     ) -> Result<(), Ics20Error> {
+        let available = self.balance(account, &amt.denom);
+        let balance = available
+            .checked_sub(amt.amount)
+            .ok_or_else(|| Ics20Error::insufficient_funds(amt.clone(), available))?;
+        self.balances
+            .insert((account.clone(), amt.denom.clone()), balance);
         Ok(())
     }
 }
@@ -227,6 +461,10 @@ impl Ics20Reader for DummyTransferModule {
         Ok(PortId::transfer())
     }
 
+    fn version(&self) -> &str {
+        &self.version
+    }
+
     fn is_send_enabled(&self) -> bool {
         true
     }
@@ -234,6 +472,80 @@ impl Ics20Reader for DummyTransferModule {
     fn is_receive_enabled(&self) -> bool {
         true
     }
+
+    fn voucher_supply(&self, denom: &PrefixedDenom) -> Amount {
+        self.balances
+            .iter()
+            .filter(|((_, balance_denom), _)| balance_denom == denom)
+            .fold(Amount::ZERO, |supply, (_, amount)| {
+                supply.saturating_add(*amount)
+            })
+    }
+
+    fn voucher_supply_cap(&self, denom: &PrefixedDenom) -> Option<Amount> {
+        self.voucher_supply_caps.get(denom).copied()
+    }
+
+    fn is_send_enabled_for(&self, denom: &PrefixedDenom) -> bool {
+        !self.disabled_denoms.contains(denom)
+    }
+
+    fn is_receive_enabled_for(&self, denom: &PrefixedDenom) -> bool {
+        !self.disabled_denoms.contains(denom)
+    }
+
+    fn translate_receiver(&self, raw: &str) -> Result<Signer, Ics20Error> {
+        match self.receiver_translations.get(raw) {
+            Some(receiver) => Ok(receiver.clone()),
+            None => raw.parse().map_err(Ics20Error::signer),
+        }
+    }
+
+    fn is_counterparty_u128_bounded(&self, _port_id: &PortId, _channel_id: ChannelId) -> bool {
+        self.u128_bounded_counterparty
+    }
+
+    fn is_strict_denom_check(&self) -> bool {
+        self.strict_denom_check
+    }
+
+    fn get_denom_trace(&self, denom_hash: &HashedDenom) -> Option<DenomTrace> {
+        self.denom_traces.get(denom_hash).cloned()
+    }
+
+    fn known_trace_prefixes(&self) -> Option<BTreeSet<TracePrefix>> {
+        self.known_trace_prefixes.clone()
+    }
+
+    fn excess_recv_decimals(&self, _denom: &PrefixedDenom) -> Option<u8> {
+        self.excess_recv_decimals
+    }
+
+    fn is_blocked_denom(&self, denom: &PrefixedDenom) -> bool {
+        self.blocked_denoms.contains(denom)
+    }
+
+    fn is_forbidden_recipient(&self, receiver: &Signer) -> bool {
+        self.forbidden_recipients.contains(receiver)
+    }
+
+    fn channel_escrow_balances(
+        &self,
+        port_id: &PortId,
+        channel_id: ChannelId,
+    ) -> Option<Vec<PrefixedCoin>> {
+        let escrow_address = self.get_channel_escrow_address(port_id, channel_id).ok()?;
+        let balances = self
+            .balances
+            .iter()
+            .filter(|((account, _), _)| *account == escrow_address)
+            .map(|((_, denom), amount)| PrefixedCoin {
+                denom: denom.clone(),
+                amount: *amount,
+            })
+            .collect();
+        Some(balances)
+    }
 }
 
 impl ChannelReader for DummyTransferModule {
@@ -306,34 +618,67 @@ impl ChannelReader for DummyTransferModule {
 
     fn get_next_sequence_recv(
         &self,
-        _port_channel_id: &(PortId, ChannelId),
+        port_channel_id: &(PortId, ChannelId),
     ) -> Result<Sequence, Error> {
-        unimplemented!()
+        match self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_recv
+            .get(port_channel_id)
+        {
+            Some(sequence) => Ok(*sequence),
+            None => Err(Error::missing_next_recv_seq(port_channel_id.clone())),
+        }
     }
 
     fn get_next_sequence_ack(
         &self,
-        _port_channel_id: &(PortId, ChannelId),
+        port_channel_id: &(PortId, ChannelId),
     ) -> Result<Sequence, Error> {
-        unimplemented!()
+        match self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .next_sequence_ack
+            .get(port_channel_id)
+        {
+            Some(sequence) => Ok(*sequence),
+            None => Err(Error::missing_next_ack_seq(port_channel_id.clone())),
+        }
     }
 
     fn get_packet_commitment(
         &self,
-        _key: &(PortId, ChannelId, Sequence),
+        key: &(PortId, ChannelId, Sequence),
     ) -> Result<PacketCommitment, Error> {
-        unimplemented!()
+        match self.ibc_store.lock().unwrap().packet_commitment.get(key) {
+            Some(commitment) => Ok(commitment.clone()),
+            None => Err(Error::packet_commitment_not_found(key.2)),
+        }
     }
 
-    fn get_packet_receipt(&self, _key: &(PortId, ChannelId, Sequence)) -> Result<Receipt, Error> {
-        unimplemented!()
+    fn get_packet_receipt(&self, key: &(PortId, ChannelId, Sequence)) -> Result<Receipt, Error> {
+        match self.ibc_store.lock().unwrap().packet_receipt.get(key) {
+            Some(receipt) => Ok(receipt.clone()),
+            None => Err(Error::packet_receipt_not_found(key.2)),
+        }
     }
 
     fn get_packet_acknowledgement(
         &self,
-        _key: &(PortId, ChannelId, Sequence),
+        key: &(PortId, ChannelId, Sequence),
     ) -> Result<AcknowledgementCommitment, Error> {
-        unimplemented!()
+        match self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .packet_acknowledgement
+            .get(key)
+        {
+            Some(ack) => Ok(ack.clone()),
+            None => Err(Error::packet_acknowledgement_not_found(key.2)),
+        }
     }
 
     fn hash(&self, value: Vec<u8>) -> Vec<u8> {
@@ -343,15 +688,34 @@ impl ChannelReader for DummyTransferModule {
     }
 
     fn host_height(&self) -> Height {
-        Height::zero()
+        self.ibc_store.lock().unwrap().host_height
     }
 
-    fn host_consensus_state(&self, _height: Height) -> Result<AnyConsensusState, Error> {
-        unimplemented!()
+    fn host_consensus_state(&self, height: Height) -> Result<AnyConsensusState, Error> {
+        match self
+            .ibc_store
+            .lock()
+            .unwrap()
+            .host_consensus_states
+            .get(&height)
+        {
+            Some(consensus_state) => Ok(consensus_state.clone()),
+            None => Err(Ics02Error::missing_local_consensus_state(height)),
+        }
+        .map_err(|e| Error::ics03_connection(Ics03Error::ics02_client(e)))
     }
 
     fn pending_host_consensus_state(&self) -> Result<AnyConsensusState, Error> {
-        unimplemented!()
+        self.host_consensus_state(self.host_height())
+    }
+
+    fn host_timestamp(&self) -> Timestamp {
+        // unlike the default, tolerate a missing host consensus state, for which the host
+        // timestamp is unknown
+        self.pending_host_consensus_state().map_or_else(
+            |_| Timestamp::none(),
+            |consensus_state| consensus_state.timestamp(),
+        )
     }
 
     fn client_update_time(
@@ -381,4 +745,13 @@ impl ChannelReader for DummyTransferModule {
 
 impl Ics20Context for DummyTransferModule {
     type AccountId = Signer;
+
+    fn forward_packet(
+        &mut self,
+        instruction: &ForwardMemo,
+        coin: &PrefixedCoin,
+    ) -> Result<(), Ics20Error> {
+        self.forwarded.push((instruction.clone(), coin.clone()));
+        Ok(())
+    }
 }